@@ -0,0 +1,28 @@
+//! Benchmarking setup for pallet-ctf
+
+use super::*;
+use frame::{deps::frame_benchmarking::v2::*, prelude::*};
+
+#[benchmarks]
+mod benchmarks {
+    use super::*;
+    #[cfg(test)]
+    use crate::pallet::Pallet as Ctf;
+    use frame_system::RawOrigin;
+
+    /// Submit a solution `d` levels above the minimum difficulty. Verification costs the same at
+    /// every difficulty, only the lowest few are mined to show it, higher ones take too long.
+    #[benchmark]
+    fn submit_solution(d: Linear<0, 2>) {
+        let caller: T::AccountId = whitelisted_caller();
+        let difficulty = T::MinDifficulty::get() + d;
+        let work = Pallet::<T>::mine(&caller, 0, difficulty);
+
+        #[extrinsic_call]
+        submit_solution(RawOrigin::Signed(caller.clone()), difficulty, work);
+
+        assert!(matches!(Score::<T>::get(&caller), ScoreState::Enabled(stats) if stats.points > 0));
+    }
+
+    impl_benchmark_test_suite!(Ctf, crate::mock::new_test_ext(), crate::mock::Test);
+}
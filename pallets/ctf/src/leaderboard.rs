@@ -0,0 +1,67 @@
+//! Leaderboard bookkeeping for the CTF pallet.
+
+use frame::prelude::*;
+
+/// Players with their scores, sorted by descending score. Among equal scores the entry that
+/// reached the score first ranks higher.
+pub type BoundedLeaderboard<AccountId, N> = BoundedVec<(AccountId, u64), N>;
+
+/// Insert `account` with `new_score` into `leaderboard`, or move its existing entry, keeping the
+/// leaderboard sorted.
+///
+/// The position is found by binary search and the entries in between are rotated into place, so
+/// an update costs O(N) shifts instead of a full re-sort. When the leaderboard is full, a new
+/// entry evicts the lowest score if it ranks above it. Returns whether `account` is on the
+/// leaderboard afterwards.
+pub fn sorted_insert_or_update<AccountId: PartialEq, N: Get<u32>>(
+    leaderboard: &mut BoundedLeaderboard<AccountId, N>,
+    account: AccountId,
+    new_score: u64,
+) -> bool {
+    match leaderboard.iter().position(|(who, _)| *who == account) {
+        Some(index) => {
+            let old_score = core::mem::replace(&mut leaderboard[index].1, new_score);
+            if new_score > old_score {
+                // Move up past every entry with a lower score
+                let position =
+                    leaderboard[..index].partition_point(|(_, score)| *score >= new_score);
+                leaderboard[position..=index].rotate_right(1);
+            } else if new_score < old_score {
+                // Move down past every entry with a score at least as high
+                let shift =
+                    leaderboard[index + 1..].partition_point(|(_, score)| *score >= new_score);
+                leaderboard[index..=index + shift].rotate_left(1);
+            }
+            true
+        }
+        None => {
+            let position = leaderboard.partition_point(|(_, score)| *score >= new_score);
+            if let Err(entry) = leaderboard.try_push((account, new_score)) {
+                // Full, the new entry takes the last place if it ranks above it
+                if position == leaderboard.len() {
+                    return false;
+                }
+                if let Some(last) = leaderboard.last_mut() {
+                    *last = entry;
+                }
+            }
+            leaderboard[position..].rotate_right(1);
+            true
+        }
+    }
+}
+
+/// Remove `account` from `leaderboard`, keeping the other entries in order. Returns whether
+/// `account` was on the leaderboard.
+pub fn remove_entry<AccountId: PartialEq, N: Get<u32>>(
+    leaderboard: &mut BoundedLeaderboard<AccountId, N>,
+    account: &AccountId,
+) -> bool {
+    match leaderboard.iter().position(|(who, _)| who == account) {
+        Some(index) => {
+            leaderboard.remove(index);
+            true
+        }
+        None => false,
+    }
+}
@@ -17,13 +17,35 @@
 
 pub use pallet::*;
 
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub mod leaderboard;
+pub mod migrations;
+pub mod runtime_api;
+pub mod weights;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
 #[frame::pallet(dev_mode)]
 pub mod pallet {
+    use crate::leaderboard::{remove_entry, sorted_insert_or_update, BoundedLeaderboard};
+    use crate::weights::WeightInfo;
     use frame::{
+        deps::frame_support::transactional,
         hashing::{blake2_256, U256},
         prelude::*,
+        traits::{Currency, Imbalance, Randomness, ReservableCurrency},
+    };
+    use sp_runtime::{
+        traits::{Printable, ValidateUnsigned},
+        transaction_validity::{TransactionSource, TransactionValidity},
+        RuntimeDebug,
     };
-    use sp_runtime::RuntimeDebug;
     use sp_std::prelude::*;
 
     /// Configure the pallet by specifying the parameters and types on which it depends.
@@ -31,42 +53,580 @@ pub mod pallet {
     pub trait Config: frame_system::Config {
         /// The overarching event type.
         type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+        /// The lowest proof-of-work difficulty accepted for a solution, worth one point.
+        #[pallet::constant]
+        type MinDifficulty: Get<u32>;
+
+        /// The highest proof-of-work difficulty accepted for a solution.
+        #[pallet::constant]
+        type MaxDifficulty: Get<u32>;
+
+        /// Times blake2-256 is applied to the proof-of-work preimage, each round hashing the
+        /// previous digest. One keeps the plain single hash.
+        #[pallet::constant]
+        type PowRounds: Get<u32>;
+
+        /// Granularity solution awards are rounded down to, never below one multiple. One keeps
+        /// awards exact.
+        #[pallet::constant]
+        type PointsGranularity: Get<u64>;
+
+        /// Share of the award kept by each further solution an account has accepted in the same
+        /// block, compounding with every one. One hundred percent disables the decay.
+        #[pallet::constant]
+        type BurstDecay: Get<Percent>;
+
+        /// Named tiers as `(minimum difficulty, name)`, a solution belongs to the highest tier
+        /// whose minimum it reaches.
+        #[pallet::constant]
+        type Tiers: Get<BoundedVec<(u32, BoundedVec<u8, ConstU32<16>>), ConstU32<8>>>;
+
+        /// The most points a single player can hold, any points credited past it are lost.
+        #[pallet::constant]
+        type MaxTotalScore: Get<u64>;
+
+        /// The most points an account can ever earn from solutions, withdrawals included.
+        /// Solutions that would take an account past it are rejected. Zero disables the cap.
+        #[pallet::constant]
+        type LifetimeScoreCap: Get<u64>;
+
+        /// Score thresholds announced with an event the first time a player reaches them.
+        #[pallet::constant]
+        type Milestones: Get<BoundedVec<u64, ConstU32<16>>>;
+
+        /// Points needed for the Bronze, Silver, Gold and Diamond player tiers, in that order.
+        #[pallet::constant]
+        type TierThresholds: Get<[u64; 4]>;
+
+        /// The number of top players kept on the leaderboard.
+        #[pallet::constant]
+        type LeaderboardSize: Get<u32>;
+
+        /// The minimum score an account must hold to enter the lottery.
+        #[pallet::constant]
+        type MinScoreForLottery: Get<u64>;
+
+        /// The number of lottery entries at which a winner is drawn.
+        #[pallet::constant]
+        type LotteryThreshold: Get<u32>;
+
+        /// The fewest entries a draw needs, smaller pools keep filling instead.
+        #[pallet::constant]
+        type MinLotteryEntrants: Get<u32>;
+
+        /// The most blocks old the output of `Randomness` may be, going by the block it reports
+        /// the output was determined at, before draws are skipped. An all-zero output, such as
+        /// [`SelfChainedRandomness`] gives without a seed, is always too old. Zero disables the
+        /// check.
+        #[pallet::constant]
+        type MaxRandomnessAge: Get<BlockNumberFor<Self>>;
+
+        /// The proof-of-work difficulty required to enter the lottery.
+        #[pallet::constant]
+        type LotteryDifficulty: Get<u32>;
+
+        /// Whether players may enter the lottery with an unsigned, fee-free transaction.
+        #[pallet::constant]
+        type AllowUnsignedLottery: Get<bool>;
+
+        /// Points charged for entering the lottery, paid into the lottery bonus pool.
+        #[pallet::constant]
+        type LotteryEntryCost: Get<u64>;
+
+        /// Balance reserved from each lottery entrant until the draw. Losers get it back, the
+        /// winner's stake is slashed to pay for the prize.
+        #[pallet::constant]
+        type LotteryStake: Get<BalanceOf<Self>>;
+
+        /// Whether a prize drawn for a withdrawn account is carried over into the lottery bonus
+        /// pool instead of being dropped.
+        #[pallet::constant]
+        type RolloverPrize: Get<bool>;
+
+        /// How far the nonce a solution was mined for may be from the account's live nonce when
+        /// it is submitted with an explicit nonce.
+        #[pallet::constant]
+        type NonceTolerance: Get<u32>;
+
+        /// Number of blocks an account must wait after an accepted solution before the next one
+        /// is scored. Zero disables the cooldown.
+        #[pallet::constant]
+        type SubmissionCooldown: Get<BlockNumberFor<Self>>;
+
+        /// Number of blocks an account must wait after a partial withdrawal before withdrawing
+        /// again. Zero disables the cooldown.
+        #[pallet::constant]
+        type WithdrawCooldown: Get<BlockNumberFor<Self>>;
+
+        /// Accepted solutions after which the game ends. Zero lets the game run indefinitely.
+        #[pallet::constant]
+        type MaxTotalSubmissions: Get<u64>;
+
+        /// Accepted solutions allowed per account in each quota period. Zero disables the quota.
+        #[pallet::constant]
+        type SubmissionQuota: Get<u32>;
+
+        /// Length in blocks of the window `SubmissionQuota` applies to.
+        #[pallet::constant]
+        type QuotaPeriod: Get<BlockNumberFor<Self>>;
+
+        /// Number of blocks points from an accepted solution are held in escrow before they are
+        /// credited, so a reorg cannot leave credit for a solution that was never included. Zero
+        /// credits points immediately.
+        #[pallet::constant]
+        type EscrowPeriod: Get<BlockNumberFor<Self>>;
+
+        /// Source of the seed used to draw the lottery winner.
+        ///
+        /// [`SelfChainedRandomness`] is available when the runtime has no better source. It only
+        /// hashes the previous value with the block number, so anyone who knows the seed can
+        /// predict every future draw; prefer a source such as BABE or a VRF where possible.
+        type Randomness: Randomness<H256, BlockNumberFor<Self>>;
+
+        /// Points credited to the author of each block.
+        #[pallet::constant]
+        type AuthorBonus: Get<u64>;
+
+        /// Currency minted to solvers on top of their points and staked by lottery entrants.
+        type Currency: ReservableCurrency<Self::AccountId>;
+
+        /// Balance minted per point awarded for a solution, zero mints nothing. Escrowed points are
+        /// rewarded once they are released.
+        #[pallet::constant]
+        type RewardPerPoint: Get<BalanceOf<Self>>;
+
+        /// What happens to points transferred to a withdrawn account.
+        #[pallet::constant]
+        type TransferToDisabled: Get<DisabledRecipientPolicy>;
+
+        /// External check consulted before a valid solution is credited, `()` allows everything.
+        type SubmissionFilter: SubmissionFilter<Self::AccountId>;
+
+        /// Whether every proof-of-work verification traces its input and hash. Only honoured by
+        /// native builds, the Wasm runtime never formats them.
+        #[pallet::constant]
+        type VerboseVerification: Get<bool>;
+
+        /// Whether the admin may import scores, for seeding a fresh chain from an export. Turn
+        /// it off once the import is done.
+        #[pallet::constant]
+        type AllowImport: Get<bool>;
+
+        /// Origin allowed to call the pallet's admin extrinsics.
+        type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Weight information for the pallet's extrinsics.
+        type WeightInfo: WeightInfo;
     }
 
+    /// Balance type of the pallet's currency.
+    pub type BalanceOf<T> =
+        <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+    /// A page of exported scores and the cursor to resume after.
+    pub type ScorePage<AccountId> = (Vec<(AccountId, ScoreState)>, Option<AccountId>);
+
+    /// The semantic version of the pallet's feature set, kept equal to the crate version.
+    pub const PALLET_VERSION: (u8, u8, u8) = (1, 0, 0);
+
+    /// Weight charged per difficulty level of a submitted solution, on top of the base weight.
+    pub const WEIGHT_PER_DIFFICULTY: u64 = 1_000_000;
+
+    /// The most candidate `work` values [`Pallet::verify_batch_pow`] checks in one call.
+    pub const MAX_BATCH_CANDIDATES: usize = 100;
+
+    /// The most accounts whose escrowed points can fall due in the same block.
+    pub const MAX_ESCROW_RELEASES_PER_BLOCK: u32 = 256;
+
+    /// The in-code storage version.
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(4);
+
     #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
+    /// A player's points and submission history
+    #[derive(
+        Encode,
+        Decode,
+        DecodeWithMemTracking,
+        Clone,
+        Default,
+        PartialEq,
+        Eq,
+        RuntimeDebug,
+        MaxEncodedLen,
+        TypeInfo,
+    )]
+    pub struct PlayerStats {
+        /// Current point total
+        pub points: u64,
+        /// Number of accepted solutions
+        pub submissions: u32,
+        /// Average difficulty of the accepted solutions, times 100
+        pub avg_difficulty_times_100: u32,
+    }
+
+    impl PlayerStats {
+        /// Stats with `points` and no submission history
+        pub fn with_points(points: u64) -> Self {
+            PlayerStats {
+                points,
+                ..Default::default()
+            }
+        }
+
+        /// Count an accepted solution of `difficulty` in the running average
+        pub fn record_submission(&mut self, difficulty: u32) {
+            let prev_count = self.submissions;
+            let new_count = prev_count.saturating_add(1);
+            let total = (self.avg_difficulty_times_100 as u64)
+                .saturating_mul(prev_count as u64)
+                .saturating_add((difficulty as u64).saturating_mul(100));
+            self.avg_difficulty_times_100 = (total / new_count as u64).saturated_into();
+            self.submissions = new_count;
+        }
+
+        /// Points weighted by the average difficulty, relative to difficulty 20
+        pub fn reputation_score(&self) -> u64 {
+            self.points
+                .saturating_mul(self.avg_difficulty_times_100 as u64)
+                / (20 * 100)
+        }
+    }
+
     /// Enum to track player score state
-    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+    #[derive(
+        Encode,
+        Decode,
+        DecodeWithMemTracking,
+        Clone,
+        PartialEq,
+        Eq,
+        RuntimeDebug,
+        MaxEncodedLen,
+        TypeInfo,
+    )]
     pub enum ScoreState {
-        /// Player has enabled score with current point total
-        Enabled(u64),
+        /// Player has enabled score with current stats
+        Enabled(PlayerStats),
         /// Player has withdrawn and is disabled from future submissions
         Disabled,
     }
 
     impl Default for ScoreState {
         fn default() -> Self {
-            ScoreState::Enabled(0)
+            ScoreState::Enabled(PlayerStats::default())
+        }
+    }
+
+    impl Printable for ScoreState {
+        fn print(&self) {
+            match self {
+                ScoreState::Enabled(stats) => {
+                    let mut line = sp_std::Writer::default();
+                    let _ = core::fmt::Write::write_fmt(
+                        &mut line,
+                        format_args!("Enabled({})", stats.points),
+                    );
+                    frame::deps::sp_io::misc::print_utf8(line.inner());
+                }
+                ScoreState::Disabled => "Disabled".print(),
+            }
         }
     }
 
+    /// How `transfer_points` treats a recipient that has withdrawn
+    #[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+    pub enum DisabledRecipientPolicy {
+        /// Fail the transfer with `ScoreDisabled`
+        Reject,
+        /// Debit the sender and credit no one
+        Burn,
+    }
+
+    /// A player's standing by total points, kept once reached
+    #[derive(
+        Encode,
+        Decode,
+        DecodeWithMemTracking,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        RuntimeDebug,
+        MaxEncodedLen,
+        TypeInfo,
+    )]
+    pub enum BadgeTier {
+        /// Reached the first of `TierThresholds`
+        Bronze,
+        /// Reached the second of `TierThresholds`
+        Silver,
+        /// Reached the third of `TierThresholds`
+        Gold,
+        /// Reached the last of `TierThresholds`
+        Diamond,
+    }
+
+    /// Why a lottery draw ended without a winner
+    #[derive(
+        Encode, Decode, DecodeWithMemTracking, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo,
+    )]
+    pub enum LotteryNoWinnerReason {
+        /// The pool had fewer entries than `MinLotteryEntrants`
+        TooFewEntrants,
+        /// The randomness is more than `MaxRandomnessAge` blocks old, or was never seeded
+        StaleRandomness,
+        /// The draw was due but the pool had no entries
+        EmptyPool,
+        /// The drawn account has withdrawn and `RolloverPrize` is off, the prize was dropped
+        WinnerWithdrawn,
+    }
+
     /// Storage for player scores
     #[pallet::storage]
-    pub type Score<T: Config> = StorageMap<_, Twox128, T::AccountId, ScoreState, ValueQuery>;
+    pub type Score<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, ScoreState, ValueQuery>;
+
+    /// Storage for the highest tier each player's score has reached
+    #[pallet::storage]
+    pub type PlayerTier<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, BadgeTier, OptionQuery>;
+
+    /// Storage for the number of accounts that have a score
+    #[pallet::storage]
+    pub type PlayerCount<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Storage for the total points given up through withdrawals
+    #[pallet::storage]
+    pub type TotalWithdrawn<T: Config> = StorageValue<_, u128, ValueQuery>;
+
+    /// Storage for the total points burned: sent to withdrawn accounts, cut off by
+    /// `MaxTotalScore` or escrowed for accounts that withdrew before their release
+    #[pallet::storage]
+    pub type TotalBurned<T: Config> = StorageValue<_, u128, ValueQuery>;
+
+    /// Storage for the top players by score, highest first
+    #[pallet::storage]
+    pub type Leaderboard<T: Config> =
+        StorageValue<_, BoundedLeaderboard<T::AccountId, T::LeaderboardSize>, ValueQuery>;
+
+    /// Storage for the block from which each account's next solution is scored again
+    #[pallet::storage]
+    pub type CooldownEndsAt<T: Config> =
+        StorageMap<_, Twox64Concat, T::AccountId, BlockNumberFor<T>, ValueQuery>;
+
+    /// Storage for the block from which each account may withdraw again after a partial
+    /// withdrawal
+    #[pallet::storage]
+    pub type WithdrawCooldownUntil<T: Config> =
+        StorageMap<_, Twox64Concat, T::AccountId, BlockNumberFor<T>, OptionQuery>;
 
-    /// Storage for lottery entries
+    /// Storage for the points each account has earned from solutions over its lifetime
     #[pallet::storage]
-    pub type LotteryEntries<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, (), OptionQuery>;
+    pub type LifetimeEarned<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, u64, ValueQuery>;
+
+    /// Storage for the hints submitted with solutions, by account and solution nonce
+    #[pallet::storage]
+    pub type SubmissionHints<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        T::AccountId,
+        Twox64Concat,
+        u32,
+        BoundedVec<u8, ConstU32<512>>,
+        OptionQuery,
+    >;
+
+    /// Storage for the nonce of each account's last accepted solution
+    #[pallet::storage]
+    pub type LastPowNonce<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32>;
+
+    /// Storage for the points each account has waiting in escrow, with the block they are
+    /// released at
+    #[pallet::storage]
+    pub type EscrowedScore<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        BoundedVec<(u64, BlockNumberFor<T>), ConstU32<64>>,
+        ValueQuery,
+    >;
+
+    /// Storage for the accounts with escrowed points falling due at each block, so releasing them
+    /// does not scan every escrow
+    #[pallet::storage]
+    pub type EscrowReleases<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<T::AccountId, ConstU32<MAX_ESCROW_RELEASES_PER_BLOCK>>,
+        ValueQuery,
+    >;
+
+    /// Storage for the milestones each account has reached, as a bitmask over `Milestones`
+    #[pallet::storage]
+    pub type ReachedMilestones<T: Config> =
+        StorageMap<_, Twox64Concat, T::AccountId, u16, ValueQuery>;
+
+    /// Storage for the start of each account's current quota window and the solutions accepted
+    /// in it
+    #[pallet::storage]
+    pub type QuotaUsed<T: Config> =
+        StorageMap<_, Twox64Concat, T::AccountId, (BlockNumberFor<T>, u32), OptionQuery>;
+
+    /// Storage for the block of each account's last accepted solution and the solutions
+    /// accepted in that block
+    #[pallet::storage]
+    pub type BlockSubmissions<T: Config> =
+        StorageMap<_, Twox64Concat, T::AccountId, (BlockNumberFor<T>, u32), OptionQuery>;
+
+    /// Storage for lottery entries with the stake reserved for each. Entries written before the
+    /// stake was tracked do not decode and count as holding nothing.
+    #[pallet::storage]
+    pub type LotteryEntries<T: Config> =
+        StorageMap<_, Twox64Concat, T::AccountId, BalanceOf<T>, OptionQuery>;
+
+    /// Storage for the number of lottery entries each account has made. Unsigned entries prove
+    /// their work against it, as the account nonce does not move for unsigned transactions.
+    #[pallet::storage]
+    pub type LotteryNonce<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
 
     /// Storage for lottery entry count
     #[pallet::storage]
     pub type LotteryEntryCount<T: Config> = StorageValue<_, u32, ValueQuery>;
 
+    /// Storage for the block the next recurring lottery draw happens at
+    #[pallet::storage]
+    pub type NextLotteryBlock<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// Storage for the number of blocks between recurring lottery draws, unset while the
+    /// lottery only draws on entry count
+    #[pallet::storage]
+    pub type LotteryInterval<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
     /// Storage for lottery randomness
     #[pallet::storage]
     pub type LotteryRandomness<T: Config> = StorageValue<_, H256, OptionQuery>;
 
+    /// Storage for the block `LotteryRandomness` was last rotated at, unset while it has no seed
+    #[pallet::storage]
+    pub type RandomnessUpdatedAt<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// Storage for points paid into the lottery, awarded to the next winner on top of the prize
+    #[pallet::storage]
+    pub type LotteryBonusPool<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    /// Storage for the number of accepted solutions per difficulty, in ascending difficulty order
+    #[pallet::storage]
+    pub type DifficultyHistogram<T: Config> =
+        StorageValue<_, BoundedVec<(u32, u32), ConstU32<237>>, ValueQuery>;
+
+    /// Storage for a 2048 bit bloom filter of the `(who, nonce)` pairs of accepted solutions,
+    /// one bit per pair. A pair whose bit is already set must meet a target twice as hard.
+    ///
+    /// The filter is never cleared, so after `n` accepted solutions a fresh pair collides with a
+    /// set bit with probability `1 - (1 - 1/2048)^n`: about 5% after 100 solutions, 39% after
+    /// 1000 and 99% after 10000, at which point nearly every solution needs the harder target.
+    #[pallet::storage]
+    pub type UsedNonces<T: Config> = StorageValue<_, [u8; 256], ValueQuery, EmptyNonceFilter>;
+
+    /// An empty `UsedNonces` filter
+    #[pallet::type_value]
+    pub fn EmptyNonceFilter() -> [u8; 256] {
+        [0; 256]
+    }
+
+    /// Storage for the accounts allowed to play while the whitelist mode is on
+    #[pallet::storage]
+    pub type OperatorWhitelist<T: Config> =
+        StorageMap<_, Twox64Concat, T::AccountId, (), OptionQuery>;
+
+    /// Storage for whether only whitelisted accounts may play, used for invite-only phases
+    #[pallet::storage]
+    pub type WhitelistMode<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Storage for unredeemed single-use codes, by the hash of the code, with their point value
+    #[pallet::storage]
+    pub type RedeemCodes<T: Config> = StorageMap<_, Blake2_128Concat, H256, u64, OptionQuery>;
+
+    /// Storage for the incident safe mode, while set only minimum difficulty solutions are
+    /// accepted, for a single point each
+    #[pallet::storage]
+    pub type SafeMode<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Storage for the version of the proof-of-work hash construction, hashed in front of every
+    /// proof so miners built for another version fail visibly
+    #[pallet::storage]
+    pub type PowVersion<T: Config> = StorageValue<_, u8, ValueQuery, InitialPowVersion>;
+
+    /// Storage for the previous proof-of-work version and the last block solutions mined for it
+    /// are still accepted, set while a version change is in its grace period
+    #[pallet::storage]
+    pub type PowVersionGrace<T: Config> = StorageValue<_, (u8, BlockNumberFor<T>), OptionQuery>;
+
+    /// The proof-of-work version a new chain starts at
+    #[pallet::type_value]
+    pub fn InitialPowVersion() -> u8 {
+        1
+    }
+
+    /// Storage for the number of solutions accepted since the game started
+    #[pallet::storage]
+    pub type TotalSubmissions<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    /// Storage for whether the game ended after `MaxTotalSubmissions` solutions. Players can
+    /// still withdraw, but no longer submit or enter the lottery.
+    #[pallet::storage]
+    pub type GameOver<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Storage for the emergency stop, while set every call except pause and unpause fails
+    #[pallet::storage]
+    pub type PalletPaused<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// Storage for the most recent proof verifications as `(who, difficulty, valid, block)`,
+    /// oldest first
+    #[pallet::storage]
+    pub type VerificationLog<T: Config> = StorageValue<
+        _,
+        BoundedVec<(T::AccountId, u32, bool, BlockNumberFor<T>), ConstU32<1000>>,
+        ValueQuery,
+    >;
+
+    /// Storage for the most recent lottery winners, oldest first
+    #[pallet::storage]
+    pub type WinnerHistory<T: Config> = StorageValue<
+        _,
+        BoundedVec<(T::AccountId, u64, BlockNumberFor<T>), ConstU32<50>>,
+        ValueQuery,
+    >;
+
+    #[pallet::genesis_config]
+    #[derive(DefaultNoBound)]
+    pub struct GenesisConfig<T: Config> {
+        /// Seed for the lottery randomness. Only meant for test chains that need predictable
+        /// draws; production chains leave it unset.
+        pub lottery_randomness: Option<H256>,
+        #[serde(skip)]
+        pub _config: PhantomData<T>,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            if let Some(seed) = self.lottery_randomness {
+                LotteryRandomness::<T>::put(seed);
+                RandomnessUpdatedAt::<T>::put(BlockNumberFor::<T>::zero());
+            }
+        }
+    }
+
     /// The pallet's events
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -76,19 +636,135 @@ pub mod pallet {
             who: T::AccountId,
             difficulty: u32,
             new_score: u64,
+            tier: Option<Vec<u8>>,
+        },
+        /// A solution's proof-of-work was invalid, the attempt is kept in the verification log
+        SolutionRejected { who: T::AccountId, difficulty: u32 },
+        /// A reward was not minted because it would leave an account without balance below the
+        /// existential deposit
+        RewardBelowExistentialDeposit {
+            who: T::AccountId,
+            reward: BalanceOf<T>,
+        },
+        /// A player's score reached a milestone for the first time
+        MilestoneReached { who: T::AccountId, milestone: u64 },
+        /// A solution was submitted during the cooldown and was not scored
+        CooldownActive {
+            who: T::AccountId,
+            blocks_remaining: u32,
+        },
+        /// Escrowed points were credited to a player's score
+        EscrowReleased { who: T::AccountId, points: u64 },
+        /// A player has withdrawn their points, `rank` is their 1-based leaderboard position at
+        /// the time if they were on it
+        Withdrawn {
+            who: T::AccountId,
+            points: u64,
+            rank: Option<u32>,
         },
-        /// A player has withdrawn their points
-        Withdrawn { who: T::AccountId, points: u64 },
         /// A lottery entry was added
         LotteryEntryAdded {
             who: T::AccountId,
             entry_number: u32,
         },
+        /// A lottery draw was skipped, the entries stay in the pool
+        LotteryNoWinner { reason: LotteryNoWinnerReason },
+        /// A prize that could not be paid was added to the lottery bonus pool
+        PrizeRolledOver { amount: u64 },
         /// A lottery winner was selected
         LotteryWinnerSelected {
             who: T::AccountId,
             points_awarded: u64,
         },
+        /// An account was added to the whitelist
+        AddedToWhitelist { who: T::AccountId },
+        /// An account was removed from the whitelist
+        RemovedFromWhitelist { who: T::AccountId },
+        /// The whitelist mode was switched on or off
+        WhitelistModeSet { enabled: bool },
+        /// Redeemable codes were created
+        CodesCreated { count: u32 },
+        /// A code was redeemed for points
+        CodeRedeemed { who: T::AccountId, value: u64 },
+        /// An account's points were moved into another account by the admin
+        ScoresMerged {
+            from: T::AccountId,
+            into: T::AccountId,
+            moved: u64,
+        },
+        /// Points were transferred between players
+        PointsTransferred {
+            from: T::AccountId,
+            to: T::AccountId,
+            amount: u64,
+        },
+        /// Points sent to or escrowed for a withdrawn account were burned
+        PointsBurned { amount: u64 },
+        /// The block author was credited with bonus points
+        AuthorBonusAwarded { author: T::AccountId, points: u64 },
+        /// Safe mode was switched on or off
+        SafeModeChanged { on: bool },
+        /// The proof-of-work hash construction moved to a new version
+        PowVersionChanged { new_version: u8 },
+        /// Recurring lottery draws were started every `interval` blocks, or stopped for zero
+        RecurringLotterySet { interval: BlockNumberFor<T> },
+        /// The lottery was reset by the admin and its bonus pool paid to `target`
+        LotteryDrained { target: T::AccountId, amount: u64 },
+        /// Points were granted to a batch of accounts, withdrawn ones are not counted
+        AirdropCompleted {
+            recipient_count: u32,
+            total_points: u64,
+        },
+        /// The pallet was paused by the admin
+        Paused,
+        /// The pallet was unpaused by the admin
+        Unpaused,
+        /// A proof was checked without being scored
+        ProofVerified {
+            who: T::AccountId,
+            difficulty: u32,
+            valid: bool,
+        },
+        /// A practice solution was checked, nothing was recorded
+        PracticeResult {
+            who: T::AccountId,
+            difficulty: u32,
+            valid: bool,
+        },
+        /// A player earned the most points `LifetimeScoreCap` allows
+        LifetimeCapReached { who: T::AccountId },
+        /// Points credited to a player were cut to `MaxTotalScore`
+        ScoreCapped {
+            who: T::AccountId,
+            attempted: u64,
+            capped: u64,
+        },
+        /// A player's score reached a higher tier
+        TierUpgrade {
+            who: T::AccountId,
+            from: Option<BadgeTier>,
+            to: BadgeTier,
+        },
+        /// The game ended after its last accepted solution
+        GameEnded { total_submissions: u64 },
+        /// A solution was submitted with a hint, identified by its hash
+        SolutionWithHint {
+            who: T::AccountId,
+            difficulty: u32,
+            hint_hash: H256,
+        },
+        /// The lottery filled up and is drawn at the start of the next block
+        LotteryThresholdReached { entry_count: u32 },
+        /// The admin imported a batch of scores
+        ScoresImported { count: u32 },
+        /// A player has withdrawn part of their points and keeps playing
+        PartiallyWithdrawn {
+            who: T::AccountId,
+            points: u64,
+            remaining: u64,
+        },
+        /// A player left the lottery before the draw
+        LotteryEntryRemoved { who: T::AccountId, new_count: u32 },
     }
 
     /// The pallet's errors
@@ -102,10 +778,52 @@ pub mod pallet {
         AlreadyWithdrawn,
         /// The account's score is disabled
         ScoreDisabled,
-        /// The difficulty is not exactly 25 for lottery entry
-        InvalidLotteryDifficulty,
         /// Failed to add lottery entry
         LotteryEntryFailed,
+        /// The account is already entered in the current lottery
+        AlreadyInLottery,
+        /// The account does not hold enough points to enter the lottery
+        InsufficientPointsForLottery,
+        /// The account does not have the points to spend
+        InsufficientScore,
+        /// The account has never earned a score
+        NeverPlayed,
+        /// The whitelist mode is on and the account is not whitelisted
+        NotWhitelisted,
+        /// The code does not exist or was already redeemed
+        InvalidCode,
+        /// The pallet is paused
+        PalletPaused,
+        /// An account's score cannot be merged into itself
+        MergeIntoSelf,
+        /// The account used up its submission quota for the current period
+        QuotaExceeded,
+        /// Points cannot be transferred to the sender
+        TransferToSelf,
+        /// Unsigned lottery entries are not allowed
+        UnsignedLotteryDisabled,
+        /// The account has too many solutions waiting in escrow
+        EscrowFull,
+        /// More candidates than `MAX_BATCH_CANDIDATES` were given to check at once
+        TooManyCandidates,
+        /// The submission filter rejected the solution
+        SubmissionVetoed,
+        /// The proof-of-work version cannot be raised any further
+        PowVersionExhausted,
+        /// The nonce of the solution is too far from the account's live nonce
+        NonceOutOfWindow,
+        /// The account withdrew recently and must wait before withdrawing again
+        WithdrawCooldownActive,
+        /// Importing scores is disabled
+        ImportDisabled,
+        /// The game has ended
+        GameOver,
+        /// The account has earned the most points `LifetimeScoreCap` allows
+        LifetimeCapReached,
+        /// The solution's nonce is not past the nonce of the account's last accepted solution
+        NonceNotAdvanced,
+        /// The account is not entered in the lottery
+        NotInLottery,
     }
 
     #[pallet::hooks]
@@ -121,13 +839,56 @@ pub mod pallet {
                 H256::from(blake2_256(&input))
             });
 
+            if new_randomness.is_some() {
+                RandomnessUpdatedAt::<T>::put(n);
+            }
             LotteryRandomness::<T>::mutate(|rand| *rand = new_randomness);
 
-            if LotteryEntryCount::<T>::get() >= 20 {
-                let _ = Self::select_lottery_winner();
+            let weight = Self::release_escrow(n);
+
+            // End the grace period of the previous proof-of-work version
+            if PowVersionGrace::<T>::get().is_some_and(|(_, grace_end)| grace_end < n) {
+                PowVersionGrace::<T>::kill();
+            }
+
+            // A recurring draw is due regardless of the number of entries
+            let recurring_due = NextLotteryBlock::<T>::get() == Some(n);
+            if recurring_due {
+                if let Some(interval) = LotteryInterval::<T>::get() {
+                    NextLotteryBlock::<T>::put(n.saturating_add(interval));
+                }
+            }
+
+            if recurring_due || LotteryEntryCount::<T>::get() >= T::LotteryThreshold::get() {
+                // Stuck randomness makes the draw predictable, keep the pool until it moves again
+                if Self::randomness_is_stale(n) {
+                    Self::deposit_event(Event::LotteryNoWinner {
+                        reason: LotteryNoWinnerReason::StaleRandomness,
+                    });
+                } else {
+                    let _ = Self::select_lottery_winner();
+                }
             }
 
-            Weight::zero()
+            weight
+        }
+
+        fn integrity_test() {
+            let crate_version = <Pallet<T> as PalletInfoAccess>::crate_version();
+            assert_eq!(
+                PALLET_VERSION,
+                (
+                    crate_version.major as u8,
+                    crate_version.minor,
+                    crate_version.patch
+                ),
+                "PALLET_VERSION must match the crate version"
+            );
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn try_state(_n: BlockNumberFor<T>) -> Result<(), sp_runtime::TryRuntimeError> {
+            Self::do_try_state()
         }
     }
 
@@ -135,54 +896,13 @@ pub mod pallet {
     impl<T: Config> Pallet<T> {
         /// Submit a solution to the PoW challenge
         #[pallet::call_index(0)]
-        #[pallet::weight(100_000_000)]
+        #[pallet::weight(T::WeightInfo::submit_solution(*difficulty))]
         pub fn submit_solution(
             origin: OriginFor<T>,
             difficulty: u32,
             work: T::Hash,
         ) -> DispatchResult {
-            // Check that the extrinsic was signed and get the signer
-            let who = ensure_signed(origin)?;
-
-            // Ensure difficulty is within valid range
-            ensure!(
-                (20..=256).contains(&difficulty),
-                Error::<T>::InvalidDifficulty
-            );
-
-            // Get the current score state
-            let score_state = Score::<T>::get(&who);
-
-            // Ensure account is not disabled
-            if score_state == ScoreState::Disabled {
-                return Err(Error::<T>::ScoreDisabled.into());
-            }
-
-            let tx_nonce = frame_system::Pallet::<T>::account_nonce(&who);
-            let tx_nonce: u32 = tx_nonce.try_into().map_err(|_| Error::<T>::BadProof)?;
-            // Verify the proof-of-work
-            let is_valid = Self::verify_pow(&who, tx_nonce, difficulty, &work)?;
-            ensure!(is_valid, Error::<T>::BadProof);
-
-            // Update the player's score
-            let mut points = match score_state {
-                ScoreState::Enabled(pts) => pts,
-                _ => 0,
-            };
-
-            let added = 1u64 << (difficulty - 20);
-            points = points.saturating_add(added);
-
-            // Update the storage
-            Score::<T>::insert(&who, ScoreState::Enabled(points));
-
-            // Emit an event
-            Self::deposit_event(Event::SolutionAccepted {
-                who,
-                difficulty,
-                new_score: points,
-            });
-
+            Self::do_submit_solution(origin, difficulty, None, None, work)?;
             Ok(())
         }
 
@@ -190,187 +910,1767 @@ pub mod pallet {
         #[pallet::call_index(1)]
         #[pallet::weight(100_000_000)]
         pub fn withdraw(origin: OriginFor<T>) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
             // Check that the extrinsic was signed and get the signer
             let who = ensure_signed(origin)?;
 
+            // Accounts that never played have nothing to withdraw
+            ensure!(Score::<T>::contains_key(&who), Error::<T>::NeverPlayed);
+
+            Self::ensure_withdraw_cooldown_over(&who)?;
+
             // Get the current score state
             let score_state = Score::<T>::get(&who);
 
             // Ensure account is not already disabled
             match score_state {
                 ScoreState::Disabled => return Err(Error::<T>::AlreadyWithdrawn.into()),
-                ScoreState::Enabled(points) => {
+                ScoreState::Enabled(PlayerStats { points, .. }) => {
+                    // Capture the rank the player withdraws at
+                    let rank = Leaderboard::<T>::get()
+                        .iter()
+                        .position(|(account, _)| *account == who)
+                        .map(|index| index as u32 + 1);
+
                     // Set the account state to Disabled
-                    Score::<T>::insert(&who, ScoreState::Disabled);
+                    Self::set_score(&who, ScoreState::Disabled);
+                    WithdrawCooldownUntil::<T>::remove(&who);
+
+                    // A withdrawn account cannot collect a prize, release its lottery stake
+                    Self::remove_lottery_entry(&who);
+
+                    // Account the withdrawn points for reconciliation
+                    TotalWithdrawn::<T>::mutate(|total| {
+                        *total = total.saturating_add(points as u128)
+                    });
 
                     // Emit an event
-                    Self::deposit_event(Event::Withdrawn { who, points });
+                    Self::deposit_event(Event::Withdrawn { who, points, rank });
 
                     Ok(())
                 }
             }
         }
 
-        /// Enter the lottery with a proof-of-work of difficulty 25
+        /// Enter the lottery with a proof-of-work of the configured lottery difficulty
         #[pallet::call_index(2)]
         #[pallet::weight(100_000_000)]
         pub fn enter_lottery(origin: OriginFor<T>, work: T::Hash) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
             // Check that the extrinsic was signed and get the signer
             let who = ensure_signed(origin)?;
 
-            // Get the current score state
-            let score_state = Score::<T>::get(&who);
+            let nonce = frame_system::Pallet::<T>::account_nonce(&who);
+            let nonce: u32 = nonce.try_into().map_err(|_| Error::<T>::BadProof)?;
+            Self::do_enter_lottery(who, nonce, work, true)
+        }
 
-            // Ensure account is not disabled
-            if score_state == ScoreState::Disabled {
-                return Err(Error::<T>::ScoreDisabled.into());
-            }
+        /// Allow an account to play while the whitelist mode is on
+        #[pallet::call_index(3)]
+        #[pallet::weight(100_000_000)]
+        pub fn add_to_whitelist(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
 
-            // Fixed difficulty of 25 for lottery entry
-            let difficulty = 25u32;
+            Self::ensure_admin(origin)?;
 
-            let tx_nonce = frame_system::Pallet::<T>::account_nonce(&who);
-            let tx_nonce: u32 = tx_nonce.try_into().map_err(|_| Error::<T>::BadProof)?;
+            OperatorWhitelist::<T>::insert(&who, ());
+            Self::deposit_event(Event::AddedToWhitelist { who });
 
-            // Verify the proof-of-work
-            let is_valid = Self::verify_pow(&who, tx_nonce, difficulty, &work)?;
-            ensure!(is_valid, Error::<T>::BadProof);
+            Ok(())
+        }
 
-            // Add to lottery
-            Self::add_lottery_entry(who)?;
+        /// Revoke an account's place on the whitelist
+        #[pallet::call_index(4)]
+        #[pallet::weight(100_000_000)]
+        pub fn remove_from_whitelist(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
+            Self::ensure_admin(origin)?;
+
+            OperatorWhitelist::<T>::remove(&who);
+            Self::deposit_event(Event::RemovedFromWhitelist { who });
 
             Ok(())
         }
-    }
-
-    impl<T: Config> Pallet<T> {
-        /// Add a lottery entry for the account
-        fn add_lottery_entry(who: T::AccountId) -> DispatchResult {
-            // Get the current entry count
-            let entry_count = LotteryEntryCount::<T>::get();
 
-            // Check if the account has already been added
-            if LotteryEntries::<T>::contains_key(&who) {
-                return Err(Error::<T>::LotteryEntryFailed.into());
-            }
+        /// Switch the whitelist mode on for an invite-only phase, or off for the public launch
+        #[pallet::call_index(5)]
+        #[pallet::weight(100_000_000)]
+        pub fn set_whitelist_mode(origin: OriginFor<T>, enabled: bool) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
 
-            // Add the entry to the lottery
-            LotteryEntries::<T>::insert(&who, ());
+            Self::ensure_admin(origin)?;
 
-            // Increment the entry count
-            let new_entry_count = entry_count.saturating_add(1);
-            LotteryEntryCount::<T>::put(new_entry_count);
+            WhitelistMode::<T>::put(enabled);
+            Self::deposit_event(Event::WhitelistModeSet { enabled });
 
-            // Emit an event for the new entry
-            Self::deposit_event(Event::LotteryEntryAdded {
-                who: who.clone(),
-                entry_number: entry_count,
-            });
             Ok(())
         }
 
-        /// Select a lottery winner
-        fn select_lottery_winner() -> DispatchResult {
-            let randomness = LotteryRandomness::<T>::get();
+        /// Create single-use codes, given as `(blake2_256(code), points)`, for off-chain events
+        #[pallet::call_index(6)]
+        #[pallet::weight(100_000_000)]
+        pub fn create_codes(origin: OriginFor<T>, codes: Vec<(H256, u64)>) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
 
-            // Get the entry count
-            let entry_count = LotteryEntryCount::<T>::get();
+            Self::ensure_admin(origin)?;
 
-            let winner_index = randomness
-                .map(|rand| {
-                    // Convert hash to a number and take modulo of entry count
-                    let rand_bytes = rand.as_ref();
-                    let rand_number = u32::from_be_bytes([
-                        rand_bytes[0],
-                        rand_bytes[1],
-                        rand_bytes[2],
-                        rand_bytes[3],
-                    ]);
-                    rand_number % entry_count
-                })
-                .unwrap_or_default();
-
-            // Iterate through the entries and find the winner
-            let mut winner: Option<T::AccountId> = None;
-            let mut iter = LotteryEntries::<T>::iter();
-            for i in 0..entry_count {
-                if let Some((entry, _)) = iter.next() {
-                    if i == winner_index {
-                        winner = Some(entry);
-                    } else {
-                        LotteryEntries::<T>::remove(entry);
-                    }
-                }
+            let count = codes.len() as u32;
+            for (code_hash, value) in codes {
+                RedeemCodes::<T>::insert(code_hash, value);
             }
-            if let Some(winner) = winner {
-                // Calculate the points to award
-                let points_to_award = 25u64 * (1 << 5);
+            Self::deposit_event(Event::CodesCreated { count });
 
-                // Update the winner's score
-                let score_state = Score::<T>::get(&winner);
-                let current_points = match score_state {
-                    ScoreState::Enabled(pts) => pts,
-                    _ => return Err(Error::<T>::AlreadyWithdrawn.into()),
-                };
+            Ok(())
+        }
 
-                let new_points = current_points.saturating_add(points_to_award);
-                Score::<T>::insert(&winner, ScoreState::Enabled(new_points));
+        /// Redeem a single-use code for the points it is worth
+        #[pallet::call_index(7)]
+        #[pallet::weight(100_000_000)]
+        pub fn redeem(
+            origin: OriginFor<T>,
+            preimage: BoundedVec<u8, ConstU32<64>>,
+        ) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
 
-                // Emit an event
-                Self::deposit_event(Event::LotteryWinnerSelected {
-                    who: winner,
-                    points_awarded: points_to_award,
-                });
-            }
+            // Check that the extrinsic was signed and get the signer
+            let who = ensure_signed(origin)?;
 
-            // Reset the lottery
-            LotteryEntryCount::<T>::put(0u32);
+            // Only whitelisted accounts may play while the whitelist mode is on
+            Self::ensure_whitelisted(&who)?;
+
+            // Ensure account is not disabled
+            let mut stats = Self::ensure_enabled(&who)?;
+
+            // Look up the code and burn it, each code can only be redeemed once
+            let code_hash = H256::from(blake2_256(&preimage));
+            let value = RedeemCodes::<T>::take(code_hash).ok_or(Error::<T>::InvalidCode)?;
+
+            // Credit the player
+            Self::add_points(&who, &mut stats, value);
+            Self::record_milestones(&who, stats.points);
+            Self::set_score(&who, ScoreState::Enabled(stats));
+
+            Self::deposit_event(Event::CodeRedeemed { who, value });
 
             Ok(())
         }
 
-        /// Verify the proof-of-work
-        fn verify_pow(
-            who: &T::AccountId,
-            nonce: u32,
+        /// Check a proof-of-work exactly like `submit_solution` would, without scoring it or
+        /// touching the cooldown, so miners can test their implementation against the chain
+        #[pallet::call_index(8)]
+        #[pallet::weight(100_000_000)]
+        pub fn verify_pow_dry_run(
+            origin: OriginFor<T>,
             difficulty: u32,
-            work: &T::Hash,
-        ) -> Result<bool, Error<T>> {
-            // Convert input values to bytes for hashing
-            let who_bytes = who.encode();
-            let nonce_bytes = nonce.encode();
+            work: T::Hash,
+        ) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
+            // Check that the extrinsic was signed and get the signer
+            let who = ensure_signed(origin)?;
+
+            // Ensure difficulty is within valid range
+            ensure!(
+                (T::MinDifficulty::get()..=T::MaxDifficulty::get()).contains(&difficulty),
+                Error::<T>::InvalidDifficulty
+            );
+
+            let tx_nonce = frame_system::Pallet::<T>::account_nonce(&who);
+            let tx_nonce: u32 = tx_nonce.try_into().map_err(|_| Error::<T>::BadProof)?;
+            let valid = Self::verify_pow(&who, tx_nonce, difficulty, &work)?;
+
+            Self::deposit_event(Event::ProofVerified {
+                who,
+                difficulty,
+                valid,
+            });
+
+            Ok(())
+        }
+
+        /// Submit a solution against an explicit target, read as a big-endian number, allowing
+        /// difficulties between the integer ones. The solution is hashed and scored as the
+        /// hardest integer difficulty whose target is at least `target`.
+        #[pallet::call_index(9)]
+        #[pallet::weight(
+            T::WeightInfo::submit_solution(Pallet::<T>::target_difficulty(target).unwrap_or(0))
+        )]
+        pub fn submit_solution_target(
+            origin: OriginFor<T>,
+            target: H256,
+            work: T::Hash,
+        ) -> DispatchResult {
+            Self::ensure_not_paused()?;
+            let difficulty =
+                Self::target_difficulty(&target).ok_or(Error::<T>::InvalidDifficulty)?;
+            let target = U256::from_big_endian(target.as_bytes());
+            Self::do_submit_solution(origin, difficulty, Some(target), None, work)?;
+            Ok(())
+        }
+
+        /// Halt every state-changing call until the pallet is unpaused
+        #[pallet::call_index(10)]
+        #[pallet::weight(100_000_000)]
+        pub fn pause_pallet(origin: OriginFor<T>) -> DispatchResult {
+            Self::ensure_admin(origin)?;
+
+            PalletPaused::<T>::put(true);
+            Self::deposit_event(Event::Paused);
+
+            Ok(())
+        }
+
+        /// Resume normal operation after a pause
+        #[pallet::call_index(11)]
+        #[pallet::weight(100_000_000)]
+        pub fn unpause_pallet(origin: OriginFor<T>) -> DispatchResult {
+            Self::ensure_admin(origin)?;
+
+            PalletPaused::<T>::put(false);
+            Self::deposit_event(Event::Unpaused);
+
+            Ok(())
+        }
+
+        /// Move all points of `from` into `into` and disable `from`, for players who lost their
+        /// keys
+        #[pallet::call_index(12)]
+        #[pallet::weight(100_000_000)]
+        pub fn merge_scores(
+            origin: OriginFor<T>,
+            from: T::AccountId,
+            into: T::AccountId,
+        ) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
+            Self::ensure_admin(origin)?;
+
+            ensure!(from != into, Error::<T>::MergeIntoSelf);
+
+            // Both accounts must still be playing
+            let moved = Self::ensure_enabled(&from)?.points;
+            let mut stats = Self::ensure_enabled(&into)?;
+            Self::add_points(&into, &mut stats, moved);
+
+            Self::set_score(&from, ScoreState::Disabled);
+            Self::remove_lottery_entry(&from);
+            Self::record_milestones(&into, stats.points);
+            Self::set_score(&into, ScoreState::Enabled(stats));
+
+            Self::deposit_event(Event::ScoresMerged { from, into, moved });
+
+            Ok(())
+        }
+
+        /// Switch safe mode on during an incident, or off once it is resolved
+        #[pallet::call_index(15)]
+        #[pallet::weight(100_000_000)]
+        pub fn set_safe_mode(origin: OriginFor<T>, on: bool) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
+            Self::ensure_admin(origin)?;
+
+            SafeMode::<T>::put(on);
+            Self::deposit_event(Event::SafeModeChanged { on });
+
+            Ok(())
+        }
+
+        /// Enter the lottery on behalf of `who` without a signature, so players without funds for
+        /// fees can take part. The proof-of-work is made for `who`'s `LotteryNonce`, so it enters
+        /// them at most once, and every check of the entry is run before the transaction is
+        /// accepted into the pool. As anyone can submit it, no entry cost or stake is taken.
+        #[pallet::call_index(14)]
+        #[pallet::weight(100_000_000)]
+        pub fn enter_lottery_unsigned(
+            origin: OriginFor<T>,
+            who: T::AccountId,
+            work: T::Hash,
+        ) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
+            ensure_none(origin)?;
+            ensure!(
+                T::AllowUnsignedLottery::get(),
+                Error::<T>::UnsignedLotteryDisabled
+            );
+
+            let nonce = LotteryNonce::<T>::get(&who);
+            Self::do_enter_lottery(who, nonce, work, false)
+        }
+
+        /// Transfer points to another player. Transfers to a withdrawn account are rejected or
+        /// burned depending on `TransferToDisabled`.
+        #[pallet::call_index(13)]
+        #[pallet::weight(100_000_000)]
+        pub fn transfer_points(
+            origin: OriginFor<T>,
+            to: T::AccountId,
+            amount: u64,
+        ) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
+            // Check that the extrinsic was signed and get the signer
+            let from = ensure_signed(origin)?;
+
+            ensure!(from != to, Error::<T>::TransferToSelf);
+
+            // Debit the sender, who must still be playing
+            let mut sender = Self::ensure_enabled(&from)?;
+            sender.points = sender
+                .points
+                .checked_sub(amount)
+                .ok_or(Error::<T>::InsufficientScore)?;
+
+            match Score::<T>::get(&to) {
+                ScoreState::Enabled(mut recipient) => {
+                    Self::add_points(&to, &mut recipient, amount);
+                    Self::record_milestones(&to, recipient.points);
+                    Self::set_score(&to, ScoreState::Enabled(recipient));
+                    Self::deposit_event(Event::PointsTransferred {
+                        from: from.clone(),
+                        to,
+                        amount,
+                    });
+                }
+                ScoreState::Disabled => match T::TransferToDisabled::get() {
+                    DisabledRecipientPolicy::Reject => return Err(Error::<T>::ScoreDisabled.into()),
+                    DisabledRecipientPolicy::Burn => {
+                        Self::record_burn(amount);
+                        Self::deposit_event(Event::PointsBurned { amount })
+                    }
+                },
+            }
+            Self::set_score(&from, ScoreState::Enabled(sender));
+
+            Ok(())
+        }
+
+        /// Move to the next version of the proof-of-work hash construction. Work mined for the
+        /// current version stays valid for `grace_duration` more blocks.
+        #[pallet::call_index(16)]
+        #[pallet::weight(100_000_000)]
+        pub fn bump_pow_version(
+            origin: OriginFor<T>,
+            grace_duration: BlockNumberFor<T>,
+        ) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
+            Self::ensure_admin(origin)?;
+
+            let old_version = PowVersion::<T>::get();
+            let new_version = old_version
+                .checked_add(1)
+                .ok_or(Error::<T>::PowVersionExhausted)?;
+            PowVersion::<T>::put(new_version);
+
+            // Let in-flight work for the old version land during the grace period
+            if grace_duration.is_zero() {
+                PowVersionGrace::<T>::kill();
+            } else {
+                let now = frame_system::Pallet::<T>::block_number();
+                PowVersionGrace::<T>::put((old_version, now.saturating_add(grace_duration)));
+            }
+            Self::deposit_event(Event::PowVersionChanged { new_version });
+
+            Ok(())
+        }
+
+        /// Submit a solution mined for `nonce`, which must be within `NonceTolerance` of the
+        /// account's live nonce
+        #[pallet::call_index(17)]
+        #[pallet::weight(T::WeightInfo::submit_solution(*difficulty))]
+        pub fn submit_solution_with_nonce(
+            origin: OriginFor<T>,
+            difficulty: u32,
+            nonce: u32,
+            work: T::Hash,
+        ) -> DispatchResult {
+            Self::do_submit_solution(origin, difficulty, None, Some(nonce), work)?;
+            Ok(())
+        }
+
+        /// Grant points to each of `recipients`, skipping withdrawn accounts
+        #[pallet::call_index(18)]
+        #[pallet::weight(
+            T::DbWeight::get()
+                .reads_writes(3, 3)
+                .saturating_mul(recipients.len() as u64)
+                .saturating_add(Weight::from_parts(100_000_000, 0))
+        )]
+        pub fn airdrop_points(
+            origin: OriginFor<T>,
+            recipients: BoundedVec<(T::AccountId, u64), ConstU32<1000>>,
+        ) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
+            Self::ensure_admin(origin)?;
+
+            let mut recipient_count = 0u32;
+            let mut total_points = 0u64;
+            for (who, amount) in recipients {
+                if Self::credit_points(&who, amount) {
+                    recipient_count = recipient_count.saturating_add(1);
+                    total_points = total_points.saturating_add(amount);
+                }
+            }
+
+            // A single event keeps large airdrops within the block's event budget
+            Self::deposit_event(Event::AirdropCompleted {
+                recipient_count,
+                total_points,
+            });
+
+            Ok(())
+        }
+
+        /// Sweep the lottery bonus pool to `target` and drop every entry, for when the lottery
+        /// misbehaves
+        #[pallet::call_index(19)]
+        #[pallet::weight(100_000_000)]
+        pub fn drain_lottery_to(origin: OriginFor<T>, target: T::AccountId) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
+            Self::ensure_admin(origin)?;
+
+            // Only an enabled account can receive the pool
+            Self::ensure_enabled(&target)?;
+
+            let amount = LotteryBonusPool::<T>::take();
+            Self::credit_points(&target, amount);
+            // Nobody won, every entrant gets their stake back
+            let entrants: Vec<T::AccountId> = LotteryEntries::<T>::iter_keys().collect();
+            for entrant in entrants {
+                let stake = Self::close_lottery_entry(&entrant);
+                T::Currency::unreserve(&entrant, stake);
+            }
+            LotteryEntryCount::<T>::put(0u32);
+
+            Self::deposit_event(Event::LotteryDrained { target, amount });
+
+            Ok(())
+        }
+
+        /// Draw the lottery every `interval` blocks from now on, on top of the draws triggered
+        /// by the entry count. An interval of zero stops the recurring draws.
+        #[pallet::call_index(20)]
+        #[pallet::weight(100_000_000)]
+        pub fn start_recurring_lottery(
+            origin: OriginFor<T>,
+            interval: BlockNumberFor<T>,
+        ) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
+            Self::ensure_admin(origin)?;
+
+            if interval.is_zero() {
+                LotteryInterval::<T>::kill();
+                NextLotteryBlock::<T>::kill();
+            } else {
+                let now = frame_system::Pallet::<T>::block_number();
+                LotteryInterval::<T>::put(interval);
+                NextLotteryBlock::<T>::put(now.saturating_add(interval));
+            }
+            Self::deposit_event(Event::RecurringLotterySet { interval });
+
+            Ok(())
+        }
+
+        /// Check a solution without scoring it, registering the player or using up quota, so
+        /// new players can try their miner
+        #[pallet::call_index(21)]
+        #[pallet::weight(100_000_000)]
+        pub fn submit_practice(
+            origin: OriginFor<T>,
+            difficulty: u32,
+            work: T::Hash,
+        ) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
+            // Check that the extrinsic was signed and get the signer
+            let who = ensure_signed(origin)?;
+
+            // Withdrawn accounts cannot practice either
+            Self::ensure_enabled(&who)?;
+
+            // Ensure difficulty is within valid range
+            ensure!(
+                (T::MinDifficulty::get()..=T::MaxDifficulty::get()).contains(&difficulty),
+                Error::<T>::InvalidDifficulty
+            );
+
+            let tx_nonce = frame_system::Pallet::<T>::account_nonce(&who);
+            let tx_nonce: u32 = tx_nonce.try_into().map_err(|_| Error::<T>::BadProof)?;
+            let valid = Self::verify_pow(&who, tx_nonce, difficulty, &work)?;
+
+            Self::deposit_event(Event::PracticeResult {
+                who,
+                difficulty,
+                valid,
+            });
+
+            Ok(())
+        }
+
+        /// Withdraw `points` and keep playing with the rest. Starts the withdrawal cooldown.
+        #[pallet::call_index(22)]
+        #[pallet::weight(100_000_000)]
+        pub fn withdraw_partial(origin: OriginFor<T>, points: u64) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
+            // Check that the extrinsic was signed and get the signer
+            let who = ensure_signed(origin)?;
+
+            // Accounts that never played have nothing to withdraw
+            ensure!(Score::<T>::contains_key(&who), Error::<T>::NeverPlayed);
+            let mut stats = Self::ensure_enabled(&who)?;
+            Self::ensure_withdraw_cooldown_over(&who)?;
+
+            stats.points = stats
+                .points
+                .checked_sub(points)
+                .ok_or(Error::<T>::InsufficientScore)?;
+            let remaining = stats.points;
+            Self::set_score(&who, ScoreState::Enabled(stats));
+
+            // Account the withdrawn points for reconciliation
+            TotalWithdrawn::<T>::mutate(|total| *total = total.saturating_add(points as u128));
+
+            let now = frame_system::Pallet::<T>::block_number();
+            WithdrawCooldownUntil::<T>::insert(
+                &who,
+                now.saturating_add(T::WithdrawCooldown::get()),
+            );
+
+            Self::deposit_event(Event::PartiallyWithdrawn {
+                who,
+                points,
+                remaining,
+            });
+
+            Ok(())
+        }
+
+        /// Write each imported score over the stored one. Importing the same entries again
+        /// leaves the state unchanged.
+        #[pallet::call_index(23)]
+        #[pallet::weight(
+            T::DbWeight::get()
+                .reads_writes(3, 3)
+                .saturating_mul(entries.len() as u64)
+                .saturating_add(Weight::from_parts(100_000_000, 0))
+        )]
+        pub fn import_scores(
+            origin: OriginFor<T>,
+            entries: BoundedVec<(T::AccountId, ScoreState), ConstU32<256>>,
+        ) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
+            Self::ensure_admin(origin)?;
+
+            ensure!(T::AllowImport::get(), Error::<T>::ImportDisabled);
+
+            let count = entries.len() as u32;
+            for (who, state) in entries {
+                Self::set_score(&who, state);
+            }
+
+            Self::deposit_event(Event::ScoresImported { count });
+
+            Ok(())
+        }
+
+        /// Submit a solution together with a free-form hint for the organizers. The hint is not
+        /// checked, it is kept for manual review under the account and the solution's nonce.
+        #[pallet::call_index(24)]
+        #[pallet::weight(T::WeightInfo::submit_solution(*difficulty))]
+        pub fn submit_solution_with_hint(
+            origin: OriginFor<T>,
+            difficulty: u32,
+            work: T::Hash,
+            hint: BoundedVec<u8, ConstU32<512>>,
+        ) -> DispatchResult {
+            Self::ensure_not_paused()?;
+            let who = ensure_signed(origin.clone())?;
+            let nonce = frame_system::Pallet::<T>::account_nonce(&who);
+            let nonce: u32 = nonce.try_into().map_err(|_| Error::<T>::BadProof)?;
+
+            // A solution skipped by the cooldown has nothing for the hint to explain
+            if !Self::do_submit_solution(origin, difficulty, None, None, work)? {
+                return Ok(());
+            }
+
+            let hint_hash = H256::from(blake2_256(&hint));
+            SubmissionHints::<T>::insert(&who, nonce, hint);
+            Self::deposit_event(Event::SolutionWithHint {
+                who,
+                difficulty,
+                hint_hash,
+            });
+
+            Ok(())
+        }
+
+        /// Leave the lottery before the draw. The stake is released, the entry cost stays in the
+        /// bonus pool.
+        #[pallet::call_index(25)]
+        #[pallet::weight(100_000_000)]
+        pub fn leave_lottery(origin: OriginFor<T>) -> DispatchResult {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
+            let who = ensure_signed(origin)?;
+
+            ensure!(Self::remove_lottery_entry(&who), Error::<T>::NotInLottery);
+
+            Ok(())
+        }
+    }
+
+    #[pallet::validate_unsigned]
+    impl<T: Config> ValidateUnsigned for Pallet<T> {
+        type Call = Call<T>;
+
+        fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+            let Call::enter_lottery_unsigned { who, work } = call else {
+                return InvalidTransaction::Call.into();
+            };
+            if !T::AllowUnsignedLottery::get() {
+                return InvalidTransaction::Call.into();
+            }
+
+            // Unsigned transactions pay no fees, only let entries that would succeed into the pool
+            let nonce = LotteryNonce::<T>::get(who);
+            let checked = Self::ensure_not_paused()
+                .and_then(|_| Self::ensure_can_enter_lottery(who, nonce, work).map(|_| ()));
+            match checked {
+                Ok(()) => {}
+                Err(e) if e == Error::<T>::BadProof.into() => {
+                    return InvalidTransaction::BadProof.into()
+                }
+                Err(e) if e == Error::<T>::AlreadyInLottery.into() => {
+                    return InvalidTransaction::Stale.into()
+                }
+                Err(_) => return InvalidTransaction::Call.into(),
+            }
+
+            ValidTransaction::with_tag_prefix("CtfLottery")
+                .and_provides((who, b"lottery", nonce).encode())
+                .longevity(64)
+                .propagate(true)
+                .build()
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Score a solution of `difficulty`, verified against `target` when given and against
+        /// the target of `difficulty` otherwise, and for `nonce` when given and the live nonce
+        /// otherwise. Returns whether the solution was scored, it is not during a cooldown or when
+        /// the proof is invalid.
+        fn do_submit_solution(
+            origin: OriginFor<T>,
+            difficulty: u32,
+            target: Option<U256>,
+            nonce: Option<u32>,
+            work: T::Hash,
+        ) -> Result<bool, DispatchError> {
+            // Nothing may change while the pallet is paused
+            Self::ensure_not_paused()?;
+
+            // Check that the extrinsic was signed and get the signer
+            let who = ensure_signed(origin)?;
+
+            ensure!(!GameOver::<T>::get(), Error::<T>::GameOver);
+
+            // Only whitelisted accounts may play while the whitelist mode is on
+            Self::ensure_whitelisted(&who)?;
+
+            // Ensure difficulty is within valid range
+            ensure!(
+                (T::MinDifficulty::get()..=T::MaxDifficulty::get()).contains(&difficulty),
+                Error::<T>::InvalidDifficulty
+            );
+
+            // Safe mode only accepts the easiest solutions
+            let safe_mode = SafeMode::<T>::get();
+            ensure!(
+                !safe_mode || difficulty == Self::effective_min_difficulty(),
+                Error::<T>::InvalidDifficulty
+            );
+
+            // Ensure account is not disabled
+            let mut stats = Self::ensure_enabled(&who)?;
+
+            // Solutions submitted during the cooldown are not scored. The call still succeeds,
+            // an error would discard the event telling the player when to come back.
+            let now = frame_system::Pallet::<T>::block_number();
+            let cooldown_ends_at = CooldownEndsAt::<T>::get(&who);
+            if now < cooldown_ends_at {
+                let blocks_remaining = (cooldown_ends_at - now).saturated_into::<u32>();
+                Self::deposit_event(Event::CooldownActive {
+                    who,
+                    blocks_remaining,
+                });
+                return Ok(false);
+            }
+
+            // Ensure the account has submissions left in its quota window
+            let quota = T::SubmissionQuota::get();
+            let (window_start, used) = Self::quota_window(&who, now);
+            ensure!(quota == 0 || used < quota, Error::<T>::QuotaExceeded);
+
+            // Accounts that earned their lifetime cap cannot score anymore
+            let lifetime_cap = T::LifetimeScoreCap::get();
+            let earned = LifetimeEarned::<T>::get(&who);
+            ensure!(
+                lifetime_cap == 0 || earned < lifetime_cap,
+                Error::<T>::LifetimeCapReached
+            );
+
+            // Give other pallets the chance to reject the solution
+            ensure!(
+                T::SubmissionFilter::allow(&who, difficulty),
+                Error::<T>::SubmissionVetoed
+            );
+
+            let tx_nonce = frame_system::Pallet::<T>::account_nonce(&who);
+            let tx_nonce: u32 = tx_nonce.try_into().map_err(|_| Error::<T>::BadProof)?;
+            // Solutions mined ahead for a far future nonce are turned away
+            let tx_nonce = match nonce {
+                Some(nonce) => {
+                    ensure!(
+                        nonce.abs_diff(tx_nonce) <= T::NonceTolerance::get(),
+                        Error::<T>::NonceOutOfWindow
+                    );
+                    nonce
+                }
+                None => tx_nonce,
+            };
+            // A reorg can reset the account nonce, old solutions must stay spent regardless
+            ensure!(
+                LastPowNonce::<T>::get(&who).is_none_or(|last| tx_nonce > last),
+                Error::<T>::NonceNotAdvanced
+            );
+            // Verify the proof-of-work, against the submitted target if there is one
+            let mut target = match target {
+                Some(target) => target,
+                None => Self::difficulty_target(difficulty)?,
+            };
+            // A `(who, nonce)` pair that may have been used before must clear half the target
+            let (byte, bit) = Self::nonce_bloom_position(&who, tx_nonce);
+            let maybe_replayed = UsedNonces::<T>::get()[byte] & bit != 0;
+            if maybe_replayed {
+                target >>= 1;
+            }
+            let is_valid = Self::meets_target(&who, tx_nonce, difficulty, &work, target);
+
+            // Log the verification, evicting the oldest entry when the log is full. Invalid
+            // proofs still succeed, an error would discard their entry along with the call.
+            VerificationLog::<T>::mutate(|log| {
+                let _ = log
+                    .force_insert_keep_right(log.len(), (who.clone(), difficulty, is_valid, now));
+            });
+            if !is_valid {
+                Self::deposit_event(Event::SolutionRejected { who, difficulty });
+                return Ok(false);
+            }
+
+            // Update the player's score
+            let mut added = Self::round_award(1u64 << (difficulty - T::MinDifficulty::get()));
+            let burst = match BlockSubmissions::<T>::get(&who) {
+                Some((block, count)) if block == now => count,
+                _ => 0,
+            };
+            for _ in 0..burst {
+                added = T::BurstDecay::get().mul_floor(added);
+            }
+            if safe_mode {
+                added = added.min(1);
+            }
+            if lifetime_cap != 0 {
+                let earned = earned.saturating_add(added);
+                ensure!(earned <= lifetime_cap, Error::<T>::LifetimeCapReached);
+                LifetimeEarned::<T>::insert(&who, earned);
+                if earned == lifetime_cap {
+                    Self::deposit_event(Event::LifetimeCapReached { who: who.clone() });
+                }
+            }
+            let escrow = T::EscrowPeriod::get();
+            if escrow.is_zero() {
+                Self::add_points(&who, &mut stats, added);
+                Self::mint_reward(&who, added);
+            } else {
+                // Hold the points back until the solution is safe from reorgs
+                let release_block = now.saturating_add(escrow);
+                EscrowedScore::<T>::try_mutate(&who, |pending| {
+                    pending.try_push((added, release_block))
+                })
+                .map_err(|_| Error::<T>::EscrowFull)?;
+                EscrowReleases::<T>::try_mutate(release_block, |due| {
+                    if due.contains(&who) {
+                        return Ok(());
+                    }
+                    due.try_push(who.clone())
+                })
+                .map_err(|_| Error::<T>::EscrowFull)?;
+            }
+            stats.record_submission(difficulty);
+            let points = stats.points;
+
+            // Update the storage
+            Self::set_score(&who, ScoreState::Enabled(stats));
+
+            // Announce any milestones the new score reaches
+            Self::record_milestones(&who, points);
+
+            // Count the solution in the difficulty histogram
+            Self::record_difficulty(difficulty);
+
+            // Count the solution in the account's burst for this block
+            BlockSubmissions::<T>::insert(&who, (now, burst.saturating_add(1)));
+
+            LastPowNonce::<T>::insert(&who, tx_nonce);
+
+            // Remember the `(who, nonce)` pair in the replay filter
+            if !maybe_replayed {
+                UsedNonces::<T>::mutate(|filter| filter[byte] |= bit);
+            }
+
+            // Count the solution against the quota
+            if quota != 0 {
+                QuotaUsed::<T>::insert(&who, (window_start, used.saturating_add(1)));
+            }
+
+            // Start the cooldown before the next scored solution
+            let cooldown = T::SubmissionCooldown::get();
+            if !cooldown.is_zero() {
+                CooldownEndsAt::<T>::insert(&who, now.saturating_add(cooldown));
+            }
+
+            // Emit an event
+            Self::deposit_event(Event::SolutionAccepted {
+                who,
+                difficulty,
+                new_score: points,
+                tier: Self::tier_for(difficulty),
+            });
+
+            // End the game once the last allowed solution is in
+            let total_submissions = TotalSubmissions::<T>::mutate(|total| {
+                *total = total.saturating_add(1);
+                *total
+            });
+            if total_submissions == T::MaxTotalSubmissions::get() {
+                GameOver::<T>::put(true);
+                Self::deposit_event(Event::GameEnded { total_submissions });
+            }
+
+            Ok(true)
+        }
+
+        /// Enter `who` into the lottery with a proof-of-work of the lottery difficulty made for
+        /// `nonce`, taking the entry cost and stake when `pays`
+        fn do_enter_lottery(
+            who: T::AccountId,
+            nonce: u32,
+            work: T::Hash,
+            pays: bool,
+        ) -> DispatchResult {
+            let mut stats = Self::ensure_can_enter_lottery(&who, nonce, &work)?;
+
+            // Spend the unsigned proof, whichever way the account entered
+            LotteryNonce::<T>::mutate(&who, |nonce| *nonce = nonce.wrapping_add(1));
+
+            // Only the account itself can agree to pay for its entry
+            if !pays {
+                return Self::add_lottery_entry(who, Zero::zero());
+            }
+
+            // Pay the entry cost into the bonus pool
+            let cost = T::LotteryEntryCost::get();
+            if cost > 0 {
+                stats.points = stats
+                    .points
+                    .checked_sub(cost)
+                    .ok_or(Error::<T>::InsufficientScore)?;
+                Self::set_score(&who, ScoreState::Enabled(stats));
+                LotteryBonusPool::<T>::mutate(|pool| *pool = pool.saturating_add(cost));
+            }
+
+            // Hold the stake until the draw
+            let stake = T::LotteryStake::get();
+            T::Currency::reserve(&who, stake)?;
+
+            // Add to lottery
+            Self::add_lottery_entry(who, stake)
+        }
+
+        /// Check, without changing any state, that `who` may enter the lottery with `work` made
+        /// for `nonce`. Shared with the transaction pool, so unsigned entries that would fail are
+        /// never accepted.
+        fn ensure_can_enter_lottery(
+            who: &T::AccountId,
+            nonce: u32,
+            work: &T::Hash,
+        ) -> Result<PlayerStats, DispatchError> {
+            ensure!(!GameOver::<T>::get(), Error::<T>::GameOver);
+
+            // Only whitelisted accounts may play while the whitelist mode is on
+            Self::ensure_whitelisted(who)?;
+
+            // Ensure account is not disabled
+            let stats = Self::ensure_enabled(who)?;
+
+            // Ensure the account has earned enough points to take part
+            ensure!(
+                stats.points >= T::MinScoreForLottery::get(),
+                Error::<T>::InsufficientPointsForLottery
+            );
+
+            // Verify the proof-of-work of the configured lottery difficulty
+            let is_valid = Self::verify_pow(who, nonce, T::LotteryDifficulty::get(), work)?;
+            ensure!(is_valid, Error::<T>::BadProof);
+
+            ensure!(
+                !LotteryEntries::<T>::contains_key(who),
+                Error::<T>::AlreadyInLottery
+            );
+
+            Ok(stats)
+        }
+
+        /// Ensure `who` is not waiting out the cooldown of an earlier partial withdrawal
+        fn ensure_withdraw_cooldown_over(who: &T::AccountId) -> DispatchResult {
+            if let Some(until) = WithdrawCooldownUntil::<T>::get(who) {
+                ensure!(
+                    frame_system::Pallet::<T>::block_number() >= until,
+                    Error::<T>::WithdrawCooldownActive
+                );
+            }
+            Ok(())
+        }
+
+        /// Ensure the origin may call admin extrinsics, shared by all of them
+        pub fn ensure_admin(origin: OriginFor<T>) -> DispatchResult {
+            T::AdminOrigin::ensure_origin(origin)?;
+            Ok(())
+        }
+
+        /// The current stats of `who`, or `ScoreDisabled` if the account has withdrawn
+        pub fn ensure_enabled(who: &T::AccountId) -> Result<PlayerStats, Error<T>> {
+            match Score::<T>::get(who) {
+                ScoreState::Enabled(stats) => Ok(stats),
+                ScoreState::Disabled => Err(Error::<T>::ScoreDisabled),
+            }
+        }
+
+        /// Whether the output of `Randomness` was determined more than `MaxRandomnessAge` blocks
+        /// before block `now`
+        fn randomness_is_stale(now: BlockNumberFor<T>) -> bool {
+            let max_age = T::MaxRandomnessAge::get();
+            if max_age.is_zero() {
+                return false;
+            }
+            let (output, known_since) = T::Randomness::random(b"ctf/lottery");
+            output.is_zero() || now.saturating_sub(known_since) > max_age
+        }
+
+        /// Ensure the pallet is not paused, checked first by every call but the pause switches
+        fn ensure_not_paused() -> DispatchResult {
+            ensure!(!PalletPaused::<T>::get(), Error::<T>::PalletPaused);
+            Ok(())
+        }
+
+        /// The start of `who`'s quota window at `now` and the solutions accepted in it, starting
+        /// a new window once the previous one has run out
+        fn quota_window(who: &T::AccountId, now: BlockNumberFor<T>) -> (BlockNumberFor<T>, u32) {
+            match QuotaUsed::<T>::get(who) {
+                Some((window_start, used))
+                    if now < window_start.saturating_add(T::QuotaPeriod::get()) =>
+                {
+                    (window_start, used)
+                }
+                _ => (now, 0),
+            }
+        }
+
+        /// Ensure `who` may play, i.e. the whitelist mode is off or `who` is whitelisted
+        fn ensure_whitelisted(who: &T::AccountId) -> DispatchResult {
+            ensure!(
+                !WhitelistMode::<T>::get() || OperatorWhitelist::<T>::contains_key(who),
+                Error::<T>::NotWhitelisted
+            );
+            Ok(())
+        }
+
+        /// Check each candidate `work` value of `who` at `nonce` against `difficulty` without
+        /// changing any state, for mining clients
+        pub fn verify_batch_pow(
+            who: &T::AccountId,
+            nonce: u32,
+            difficulty: u32,
+            candidates: Vec<T::Hash>,
+        ) -> Result<Vec<bool>, Error<T>> {
+            ensure!(
+                candidates.len() <= MAX_BATCH_CANDIDATES,
+                Error::<T>::TooManyCandidates
+            );
+            candidates
+                .iter()
+                .map(|work| Self::verify_pow(who, nonce, difficulty, work))
+                .collect()
+        }
+
+        /// The highest tier `who` has reached
+        pub fn get_tier(who: &T::AccountId) -> Option<BadgeTier> {
+            PlayerTier::<T>::get(who)
+        }
+
+        /// The semantic version of the pallet's feature set
+        pub fn version() -> (u8, u8, u8) {
+            PALLET_VERSION
+        }
+
+        /// The most recent lottery winners with the points awarded and the block of the draw,
+        /// oldest first
+        pub fn recent_winners() -> Vec<(T::AccountId, u64, BlockNumberFor<T>)> {
+            WinnerHistory::<T>::get().into_inner()
+        }
+
+        /// The number of accounts that have a score, withdrawn ones included
+        pub fn player_count() -> u32 {
+            PlayerCount::<T>::get()
+        }
+
+        /// The number of lottery entries still needed before the next draw
+        pub fn lottery_draw_countdown() -> u32 {
+            T::LotteryThreshold::get().saturating_sub(LotteryEntryCount::<T>::get())
+        }
+
+        /// The total points given up through withdrawals
+        pub fn total_withdrawn() -> u128 {
+            TotalWithdrawn::<T>::get()
+        }
+
+        /// The total points burned: sent to withdrawn accounts, cut off by `MaxTotalScore` or
+        /// escrowed for accounts that withdrew before their release
+        pub fn total_burned() -> u128 {
+            TotalBurned::<T>::get()
+        }
+
+        /// Print the score state of `who` to the node log
+        pub fn debug_print_score(who: &T::AccountId) {
+            sp_runtime::print(Score::<T>::get(who));
+        }
+
+        /// The reputation of `who`, zero once the account has withdrawn
+        pub fn reputation_score(who: &T::AccountId) -> u64 {
+            match Score::<T>::get(who) {
+                ScoreState::Enabled(stats) => stats.reputation_score(),
+                ScoreState::Disabled => 0,
+            }
+        }
+
+        /// The lowest difficulty currently accepted for a solution
+        pub fn effective_min_difficulty() -> u32 {
+            T::MinDifficulty::get()
+        }
+
+        /// The name of the highest tier `difficulty` reaches, if any
+        pub fn tier_for(difficulty: u32) -> Option<Vec<u8>> {
+            T::Tiers::get()
+                .into_iter()
+                .filter(|(min_difficulty, _)| *min_difficulty <= difficulty)
+                .max_by_key(|(min_difficulty, _)| *min_difficulty)
+                .map(|(_, name)| name.into_inner())
+        }
+
+        /// The top players with their scores, highest first
+        pub fn leaderboard() -> Vec<(T::AccountId, u64)> {
+            Leaderboard::<T>::get().into_inner()
+        }
+
+        /// The enabled account with the most points, ties going to the lowest account id so the
+        /// answer does not depend on storage order. `None` when no account is enabled.
+        pub fn leader() -> Option<(T::AccountId, u64)> {
+            Score::<T>::iter()
+                .filter_map(|(who, state)| match state {
+                    ScoreState::Enabled(stats) => Some((who, stats.points)),
+                    ScoreState::Disabled => None,
+                })
+                .max_by(|(a, a_points), (b, b_points)| a_points.cmp(b_points).then(b.cmp(a)))
+        }
+
+        /// The points of `who` with the current block, for attestations signed off-chain. `None`
+        /// for accounts that never played or have withdrawn.
+        pub fn score_attestation(who: &T::AccountId) -> Option<(u64, BlockNumberFor<T>)> {
+            if !Score::<T>::contains_key(who) {
+                return None;
+            }
+            match Score::<T>::get(who) {
+                ScoreState::Enabled(stats) => {
+                    Some((stats.points, frame_system::Pallet::<T>::block_number()))
+                }
+                ScoreState::Disabled => None,
+            }
+        }
+
+        /// Up to `limit` stored scores, withdrawn ones included, following the account `start`
+        /// or from the beginning of the map. The cursor returned alongside is the last account
+        /// of the page, `None` once the map is exhausted.
+        pub fn export_scores(start: Option<T::AccountId>, limit: u32) -> ScorePage<T::AccountId> {
+            let mut iter = match start {
+                Some(start) => Score::<T>::iter_from(Score::<T>::hashed_key_for(start)),
+                None => Score::<T>::iter(),
+            };
+            let page: Vec<_> = iter.by_ref().take(limit as usize).collect();
+            let cursor = match iter.next() {
+                Some(_) => page.last().map(|(who, _)| who.clone()),
+                None => None,
+            };
+            (page, cursor)
+        }
+
+        /// The top `n` leaderboard entries with each account as its raw encoding, zero-padded to
+        /// 32 bytes so indexers need not know the account type
+        pub fn leaderboard_raw(n: u32) -> Vec<([u8; 32], u64)> {
+            Self::leaderboard()
+                .into_iter()
+                .take(n as usize)
+                .map(|(who, score)| {
+                    let encoded = who.encode();
+                    assert!(
+                        encoded.len() <= 32,
+                        "account ids must encode to at most 32 bytes"
+                    );
+                    let mut raw = [0u8; 32];
+                    raw[..encoded.len()].copy_from_slice(&encoded);
+                    (raw, score)
+                })
+                .collect()
+        }
+
+        /// The most recent proof verifications as `(who, difficulty, valid, block)`, oldest first
+        pub fn verification_log() -> Vec<(T::AccountId, u32, bool, BlockNumberFor<T>)> {
+            VerificationLog::<T>::get().into_inner()
+        }
+
+        /// The number of accepted solutions per difficulty, in ascending difficulty order
+        pub fn histogram() -> Vec<(u32, u32)> {
+            DifficultyHistogram::<T>::get().into_inner()
+        }
+
+        /// Add `amount` to the points in `stats` of `who`, holding them at `MaxTotalScore` and
+        /// burning the rest
+        fn add_points(who: &T::AccountId, stats: &mut PlayerStats, amount: u64) {
+            let attempted = stats.points.saturating_add(amount);
+            let capped = attempted.min(T::MaxTotalScore::get());
+            if capped < attempted {
+                Self::record_burn(attempted - capped);
+                Self::deposit_event(Event::ScoreCapped {
+                    who: who.clone(),
+                    attempted,
+                    capped,
+                });
+            }
+            stats.points = capped;
+        }
+
+        /// Add `amount` to the total points burned
+        fn record_burn(amount: u64) {
+            TotalBurned::<T>::mutate(|total| *total = total.saturating_add(amount as u128));
+        }
+
+        /// Write `who`'s score, counting the account as a player the first time and moving it to
+        /// its new rank, or off the leaderboard once it has withdrawn
+        fn set_score(who: &T::AccountId, state: ScoreState) {
+            if !Score::<T>::contains_key(who) {
+                PlayerCount::<T>::mutate(|count| *count = count.saturating_add(1));
+            }
+            Leaderboard::<T>::mutate(|leaderboard| match &state {
+                ScoreState::Enabled(stats) => {
+                    sorted_insert_or_update(leaderboard, who.clone(), stats.points);
+                }
+                ScoreState::Disabled => {
+                    remove_entry(leaderboard, who);
+                }
+            });
+            Score::<T>::insert(who, state);
+        }
+
+        /// Check the invariants tying the pallet's storage items together
+        #[cfg(any(feature = "try-runtime", test))]
+        pub(crate) fn do_try_state() -> Result<(), sp_runtime::TryRuntimeError> {
+            ensure!(
+                LotteryEntryCount::<T>::get() == LotteryEntries::<T>::iter_keys().count() as u32,
+                "LotteryEntryCount does not match the number of lottery entries"
+            );
+            for who in Score::<T>::iter_keys() {
+                ensure!(
+                    Score::<T>::try_get(&who).is_ok(),
+                    "a Score entry does not decode"
+                );
+            }
+            for (who, points) in Leaderboard::<T>::get() {
+                ensure!(
+                    Score::<T>::contains_key(&who),
+                    "a leaderboard entry belongs to an account that never played"
+                );
+                let ranked = match Score::<T>::get(&who) {
+                    ScoreState::Enabled(stats) => stats.points == points,
+                    ScoreState::Disabled => false,
+                };
+                ensure!(
+                    ranked,
+                    "a leaderboard entry does not match the account's score"
+                );
+            }
+            Ok(())
+        }
+
+        /// The byte and bit mask of `(who, nonce)` in `UsedNonces`
+        pub(crate) fn nonce_bloom_position(who: &T::AccountId, nonce: u32) -> (usize, u8) {
+            let who_hash = blake2_256(&who.encode());
+            let who_hash = u32::from_le_bytes([who_hash[0], who_hash[1], who_hash[2], who_hash[3]]);
+            let index = (who_hash ^ nonce) % 2048;
+            ((index / 8) as usize, 1 << (index % 8))
+        }
+
+        /// Round `award` down to a multiple of `PointsGranularity`, keeping at least one multiple
+        fn round_award(award: u64) -> u64 {
+            let granularity = T::PointsGranularity::get().max(1);
+            (award / granularity).max(1).saturating_mul(granularity)
+        }
+
+        /// Credit the escrowed points due at block `n`, forfeiting those of withdrawn accounts.
+        /// Returns the weight used.
+        fn release_escrow(n: BlockNumberFor<T>) -> Weight {
+            let accounts = EscrowReleases::<T>::take(n);
+            // Taking the index, then per account its escrow, the score writes of crediting it and
+            // the balance reward
+            let db = T::DbWeight::get();
+            let weight = db
+                .reads_writes(1, 1)
+                .saturating_add(db.reads_writes(8, 7).saturating_mul(accounts.len() as u64));
+            for who in accounts {
+                let mut released = 0u64;
+                EscrowedScore::<T>::mutate_exists(&who, |maybe_pending| {
+                    if let Some(pending) = maybe_pending {
+                        pending.retain(|(amount, release_block)| {
+                            let due = *release_block <= n;
+                            if due {
+                                released = released.saturating_add(*amount);
+                            }
+                            !due
+                        });
+                        if pending.is_empty() {
+                            *maybe_pending = None;
+                        }
+                    }
+                });
+                if released == 0 {
+                    continue;
+                }
+
+                if Self::credit_points(&who, released) {
+                    Self::mint_reward(&who, released);
+                    Self::deposit_event(Event::EscrowReleased {
+                        who,
+                        points: released,
+                    });
+                } else {
+                    Self::record_burn(released);
+                    Self::deposit_event(Event::PointsBurned { amount: released });
+                }
+            }
+            weight
+        }
+
+        /// Mint the balance reward for `points` awarded to `who` from solutions. A reward too
+        /// small to create the account is not minted, `RewardBelowExistentialDeposit` reports it.
+        fn mint_reward(who: &T::AccountId, points: u64) {
+            let reward = T::RewardPerPoint::get().saturating_mul(points.saturated_into());
+            if reward.is_zero() {
+                return;
+            }
+            // Dropping the imbalance raises the total issuance by the reward
+            if T::Currency::deposit_creating(who, reward).peek().is_zero() {
+                Self::deposit_event(Event::RewardBelowExistentialDeposit {
+                    who: who.clone(),
+                    reward,
+                });
+            }
+        }
+
+        /// Add `amount` to the score of `who` along with its milestones and leaderboard rank.
+        /// Returns false without crediting anything if the account has withdrawn.
+        fn credit_points(who: &T::AccountId, amount: u64) -> bool {
+            let ScoreState::Enabled(mut stats) = Score::<T>::get(who) else {
+                return false;
+            };
+            Self::add_points(who, &mut stats, amount);
+            let points = stats.points;
+            Self::set_score(who, ScoreState::Enabled(stats));
+            Self::record_milestones(who, points);
+            true
+        }
+
+        /// Emit `MilestoneReached` for every milestone `score` reaches that `who` had not
+        /// reached before, and move `who` up to the tier `score` reaches
+        fn record_milestones(who: &T::AccountId, score: u64) {
+            Self::record_tier(who, score);
+            ReachedMilestones::<T>::mutate(who, |reached| {
+                for (index, milestone) in T::Milestones::get().into_iter().enumerate() {
+                    let bit = 1u16 << index;
+                    if score >= milestone && *reached & bit == 0 {
+                        *reached |= bit;
+                        Self::deposit_event(Event::MilestoneReached {
+                            who: who.clone(),
+                            milestone,
+                        });
+                    }
+                }
+            });
+        }
+
+        /// Raise `who` to the tier of `score`, never lowering a tier already reached
+        fn record_tier(who: &T::AccountId, score: u64) {
+            let Some(to) = Self::badge_tier_for(score) else {
+                return;
+            };
+            let from = PlayerTier::<T>::get(who);
+            if from.is_none_or(|from| to > from) {
+                PlayerTier::<T>::insert(who, to);
+                Self::deposit_event(Event::TierUpgrade {
+                    who: who.clone(),
+                    from,
+                    to,
+                });
+            }
+        }
+
+        /// The highest tier whose threshold `score` reaches
+        fn badge_tier_for(score: u64) -> Option<BadgeTier> {
+            let tiers = [
+                BadgeTier::Bronze,
+                BadgeTier::Silver,
+                BadgeTier::Gold,
+                BadgeTier::Diamond,
+            ];
+            T::TierThresholds::get()
+                .into_iter()
+                .zip(tiers)
+                .rev()
+                .find(|(threshold, _)| score >= *threshold)
+                .map(|(_, tier)| tier)
+        }
+
+        /// Count an accepted solution of `difficulty` in the histogram
+        fn record_difficulty(difficulty: u32) {
+            DifficultyHistogram::<T>::mutate(|histogram| {
+                // The histogram is kept sorted by difficulty for the binary search
+                match histogram.binary_search_by_key(&difficulty, |(d, _)| *d) {
+                    Ok(index) => histogram[index].1 = histogram[index].1.saturating_add(1),
+                    Err(index) => {
+                        let _ = histogram.try_insert(index, (difficulty, 1));
+                    }
+                }
+            });
+        }
+
+        /// Add a lottery entry for the account holding `stake`
+        fn add_lottery_entry(who: T::AccountId, stake: BalanceOf<T>) -> DispatchResult {
+            // Get the current entry count
+            let entry_count = LotteryEntryCount::<T>::get();
+
+            // Check if the account has already been added
+            if LotteryEntries::<T>::contains_key(&who) {
+                return Err(Error::<T>::AlreadyInLottery.into());
+            }
+
+            // Increment the entry count
+            let new_entry_count = entry_count
+                .checked_add(1)
+                .ok_or(Error::<T>::LotteryEntryFailed)?;
+
+            // Add the entry to the lottery
+            LotteryEntries::<T>::insert(&who, stake);
+            LotteryEntryCount::<T>::put(new_entry_count);
+
+            // Emit an event for the new entry
+            Self::deposit_event(Event::LotteryEntryAdded {
+                who: who.clone(),
+                entry_number: entry_count,
+            });
+
+            // Warn of the draw the next block performs. The count only passes the threshold once
+            // per round, the draw resets it.
+            if new_entry_count == T::LotteryThreshold::get() {
+                Self::deposit_event(Event::LotteryThresholdReached {
+                    entry_count: new_entry_count,
+                });
+            }
+            Ok(())
+        }
+
+        /// Remove `who`'s lottery entry, returning the stake reserved for it
+        fn close_lottery_entry(who: &T::AccountId) -> BalanceOf<T> {
+            let stake = LotteryEntries::<T>::get(who).unwrap_or_default();
+            LotteryEntries::<T>::remove(who);
+            stake
+        }
+
+        /// Take `who` out of the lottery before the draw, releasing its stake. Returns whether
+        /// `who` was entered.
+        fn remove_lottery_entry(who: &T::AccountId) -> bool {
+            if !LotteryEntries::<T>::contains_key(who) {
+                return false;
+            }
+            let stake = Self::close_lottery_entry(who);
+            let new_count = LotteryEntryCount::<T>::mutate(|count| {
+                *count = count.saturating_sub(1);
+                *count
+            });
+            T::Currency::unreserve(who, stake);
+
+            Self::deposit_event(Event::LotteryEntryRemoved {
+                who: who.clone(),
+                new_count,
+            });
+            true
+        }
+
+        /// Select a lottery winner. Hooks run outside a storage layer, so a failed draw is rolled
+        /// back here rather than leaving the losers' entries removed.
+        #[transactional]
+        fn select_lottery_winner() -> DispatchResult {
+            let (randomness, _) = T::Randomness::random(b"ctf/lottery");
+
+            // Collect the entrants up front so the draw does not rely on the stored count
+            let entrants: Vec<T::AccountId> = LotteryEntries::<T>::iter_keys().collect();
+            let entry_count = entrants.len() as u32;
+
+            // Reconcile the stored count with the actual number of entries
+            let recorded_count = LotteryEntryCount::<T>::get();
+            if recorded_count != entry_count {
+                log::warn!(
+                    "lottery entry count {} does not match {} stored entries",
+                    recorded_count,
+                    entry_count
+                );
+                LotteryEntryCount::<T>::put(entry_count);
+            }
+
+            // Only a drifted count can trigger a draw without entries, there is nobody to pick
+            if entry_count == 0 {
+                Self::deposit_event(Event::LotteryNoWinner {
+                    reason: LotteryNoWinnerReason::EmptyPool,
+                });
+                return Ok(());
+            }
+
+            // A pool this small would hand the prize to whoever rushed the draw, keep filling it
+            if entry_count < T::MinLotteryEntrants::get() {
+                Self::deposit_event(Event::LotteryNoWinner {
+                    reason: LotteryNoWinnerReason::TooFewEntrants,
+                });
+                return Ok(());
+            }
+
+            // Convert hash to a number and take modulo of entry count
+            let rand_bytes = randomness.as_ref();
+            let rand_number =
+                u32::from_be_bytes([rand_bytes[0], rand_bytes[1], rand_bytes[2], rand_bytes[3]]);
+            let winner_index = rand_number.checked_rem(entry_count).unwrap_or_default();
+
+            // Close every entry, returning the stakes of everyone but the winner
+            let mut winner: Option<(T::AccountId, BalanceOf<T>)> = None;
+            for (i, entry) in entrants.into_iter().enumerate() {
+                let stake = Self::close_lottery_entry(&entry);
+                if i as u32 == winner_index {
+                    winner = Some((entry, stake));
+                } else {
+                    T::Currency::unreserve(&entry, stake);
+                }
+            }
+            if let Some((winner, stake)) = winner {
+                // The winner's stake pays for the prize, dropping the imbalance burns it
+                let _ = T::Currency::slash_reserved(&winner, stake);
+
+                // Calculate the points to award
+                let points_to_award = 25u64 * (1 << 5);
+
+                // Update the winner's score
+                let score_state = Score::<T>::get(&winner);
+                let mut stats = match score_state {
+                    ScoreState::Enabled(stats) => stats,
+                    ScoreState::Disabled if T::RolloverPrize::get() => {
+                        // Carry the prize over to the next draw and close this one
+                        LotteryBonusPool::<T>::mutate(|pool| {
+                            *pool = pool.saturating_add(points_to_award)
+                        });
+                        LotteryEntryCount::<T>::put(0u32);
+                        Self::deposit_event(Event::PrizeRolledOver {
+                            amount: points_to_award,
+                        });
+                        return Ok(());
+                    }
+                    ScoreState::Disabled => {
+                        // Drop the prize and close this draw
+                        LotteryEntryCount::<T>::put(0u32);
+                        Self::deposit_event(Event::LotteryNoWinner {
+                            reason: LotteryNoWinnerReason::WinnerWithdrawn,
+                        });
+                        return Ok(());
+                    }
+                };
+
+                // The winner also collects everything paid into the bonus pool
+                let points_to_award = points_to_award.saturating_add(LotteryBonusPool::<T>::take());
+
+                Self::add_points(&winner, &mut stats, points_to_award);
+                Self::set_score(&winner, ScoreState::Enabled(stats));
+
+                // Record the winner, evicting the oldest entry when the history is full
+                WinnerHistory::<T>::mutate(|history| {
+                    let entry = (
+                        winner.clone(),
+                        points_to_award,
+                        frame_system::Pallet::<T>::block_number(),
+                    );
+                    let _ = history.force_insert_keep_right(history.len(), entry);
+                });
+
+                // Emit an event
+                Self::deposit_event(Event::LotteryWinnerSelected {
+                    who: winner,
+                    points_awarded: points_to_award,
+                });
+            }
+
+            // Reset the lottery
+            LotteryEntryCount::<T>::put(0u32);
+
+            Ok(())
+        }
+
+        /// Verify the proof-of-work
+        fn verify_pow(
+            who: &T::AccountId,
+            nonce: u32,
+            difficulty: u32,
+            work: &T::Hash,
+        ) -> Result<bool, Error<T>> {
+            // The proof is valid if the hash value is less than the target
+            Ok(Self::meets_target(
+                who,
+                nonce,
+                difficulty,
+                work,
+                Self::difficulty_target(difficulty)?,
+            ))
+        }
+
+        /// Whether the hash of `work` is below `target` under the current proof-of-work version,
+        /// or under the previous one while its grace period lasts
+        fn meets_target(
+            who: &T::AccountId,
+            nonce: u32,
+            difficulty: u32,
+            work: &T::Hash,
+            target: U256,
+        ) -> bool {
+            if Self::pow_hash(PowVersion::<T>::get(), who, nonce, difficulty, work) < target {
+                return true;
+            }
+            match PowVersionGrace::<T>::get() {
+                Some((old_version, grace_end))
+                    if grace_end >= frame_system::Pallet::<T>::block_number() =>
+                {
+                    Self::pow_hash(old_version, who, nonce, difficulty, work) < target
+                }
+                _ => false,
+            }
+        }
+
+        /// Brute-force a `work` value of `who` at `nonce` that passes `verify_pow` for
+        /// `difficulty`, for tests and benchmarks. Only practical for low difficulties.
+        #[cfg(any(test, feature = "runtime-benchmarks"))]
+        pub fn mine(who: &T::AccountId, nonce: u32, difficulty: u32) -> T::Hash {
+            const MAX_ATTEMPTS: u64 = 1 << 24;
+
+            for attempt in 0..MAX_ATTEMPTS {
+                let mut work = T::Hash::default();
+                let bytes = work.as_mut();
+                let len = bytes.len();
+                bytes[len.saturating_sub(8)..]
+                    .copy_from_slice(&attempt.to_be_bytes()[8usize.saturating_sub(len)..]);
+                if matches!(Self::verify_pow(who, nonce, difficulty, &work), Ok(true)) {
+                    return work;
+                }
+            }
+            panic!("no work of difficulty {difficulty} found in {MAX_ATTEMPTS} attempts");
+        }
+
+        /// The exact bytes hashed to check `work` under the current proof-of-work version, for
+        /// miners to compare their own encoding against
+        pub fn pow_preimage(
+            who: &T::AccountId,
+            nonce: u32,
+            difficulty: u32,
+            work: &T::Hash,
+        ) -> Vec<u8> {
+            Self::versioned_preimage(PowVersion::<T>::get(), who, nonce, difficulty, work)
+        }
+
+        /// Times the preimage is hashed, never fewer than once
+        pub fn pow_rounds() -> u32 {
+            T::PowRounds::get().max(1)
+        }
+
+        /// The bytes hashed to check `work` under `version`
+        fn versioned_preimage(
+            version: u8,
+            who: &T::AccountId,
+            nonce: u32,
+            difficulty: u32,
+            work: &T::Hash,
+        ) -> Vec<u8> {
+            // Convert input values to bytes for hashing
+            let who_bytes = who.encode();
+            let nonce_bytes = nonce.encode();
             let difficulty_bytes = difficulty.encode();
             let work_bytes = work.as_ref();
 
-            // Concatenate the bytes
+            // Concatenate the bytes, behind the version of this construction
             let mut input = Vec::new();
+            input.push(version);
             input.extend_from_slice(&who_bytes);
             input.extend_from_slice(&nonce_bytes);
             input.extend_from_slice(&difficulty_bytes);
             input.extend_from_slice(work_bytes);
+            input
+        }
 
-            log::info!("input: {:?}", input);
-
-            // Calculate the hash
-            let hash = sp_io::hashing::blake2_256(&input);
+        /// The proof-of-work hash of `work` under `version` as a number, shared by every way of
+        /// checking a proof
+        fn pow_hash(
+            version: u8,
+            who: &T::AccountId,
+            nonce: u32,
+            difficulty: u32,
+            work: &T::Hash,
+        ) -> U256 {
+            let input = Self::versioned_preimage(version, who, nonce, difficulty, work);
 
-            log::info!("hash: {:?}", hash);
+            // Calculate the hash, rehashing the digest for every further round
+            let mut hash = sp_io::hashing::blake2_256(&input);
+            for _ in 1..Self::pow_rounds() {
+                hash = sp_io::hashing::blake2_256(&hash);
+            }
 
             // Convert the hash to a numeric value for comparison
             let hash_value = U256::from_little_endian(&hash);
 
-            log::info!("hash_value: {:?}", hash_value);
+            if cfg!(feature = "std") && T::VerboseVerification::get() {
+                log::trace!("input: {:?}", input);
+                log::trace!("hash: {:?}", hash);
+                log::trace!("hash_value: {:?}", hash_value);
+            }
+
+            hash_value
+        }
+
+        /// The hardest integer difficulty whose target is at least `target`, read as a big-endian
+        /// number, `None` for a zero target
+        pub fn target_difficulty(target: &H256) -> Option<u32> {
+            let target = U256::from_big_endian(target.as_bytes());
+            // 2^(256 - d) is at least `target` exactly for d up to the leading zeros of
+            // `target - 1`
+            target
+                .checked_sub(U256::one())
+                .map(|below| below.leading_zeros())
+        }
+
+        /// The target a proof-of-work hash of `difficulty` must stay below
+        pub fn difficulty_target(difficulty: u32) -> Result<U256, Error<T>> {
+            // No target exists past 256 bits, whatever bounds the callers check
+            ensure!(difficulty <= 256, Error::<T>::InvalidDifficulty);
 
             // Calculate the target value: 2^256 / 2^difficulty
             // This simplifies to 2^(256-difficulty)
-            let target = if difficulty < 256 {
-                U256::one() << (256 - difficulty)
+            if difficulty < 256 {
+                Ok(U256::one() << (256 - difficulty))
             } else {
-                U256::one() // If difficulty=256, target=1 (nearly impossible)
-            };
+                Ok(U256::one()) // If difficulty=256, target=1 (nearly impossible)
+            }
+        }
+    }
 
-            // The proof is valid if the hash value is less than the target
-            Ok(hash_value < target)
+    impl<T: Config> pallet_authorship::EventHandler<T::AccountId, BlockNumberFor<T>> for Pallet<T> {
+        fn note_author(author: T::AccountId) {
+            let points = T::AuthorBonus::get();
+            if points == 0 {
+                return;
+            }
+
+            // Withdrawn authors are skipped, a hook has no one to report an error to
+            if let ScoreState::Enabled(mut stats) = Score::<T>::get(&author) {
+                Self::add_points(&author, &mut stats, points);
+                Self::record_milestones(&author, stats.points);
+                Self::set_score(&author, ScoreState::Enabled(stats));
+
+                Self::deposit_event(Event::AuthorBonusAwarded { author, points });
+            }
+        }
+    }
+
+    /// Decides whether a valid solution may be credited, letting another pallet veto
+    /// submissions.
+    pub trait SubmissionFilter<AccountId> {
+        /// Whether `who` may be credited for a solution of `difficulty`.
+        fn allow(who: &AccountId, difficulty: u32) -> bool;
+    }
+
+    impl<AccountId> SubmissionFilter<AccountId> for () {
+        fn allow(_who: &AccountId, _difficulty: u32) -> bool {
+            true
+        }
+    }
+
+    /// Randomness chained from the seed in [`LotteryRandomness`], rehashed with the block number
+    /// every block and reported as determined at the last rehash. Fully predictable once the seed
+    /// is known, and zero, determined at genesis, while no seed is set.
+    pub struct SelfChainedRandomness<T>(PhantomData<T>);
+
+    impl<T: Config> Randomness<H256, BlockNumberFor<T>> for SelfChainedRandomness<T> {
+        fn random(_subject: &[u8]) -> (H256, BlockNumberFor<T>) {
+            (
+                LotteryRandomness::<T>::get().unwrap_or_default(),
+                RandomnessUpdatedAt::<T>::get().unwrap_or_default(),
+            )
         }
     }
 }
@@ -0,0 +1,259 @@
+//! Storage migrations for the CTF pallet.
+
+use crate::{Config, Pallet};
+use frame::{
+    deps::frame_support::{
+        migrations::{MigrationId, SteppedMigration, SteppedMigrationError, VersionedMigration},
+        traits::UncheckedOnRuntimeUpgrade,
+        weights::WeightMeter,
+    },
+    prelude::*,
+};
+
+/// Prefix of the identifiers of the multi-block migrations of this pallet.
+const PALLET_MIGRATIONS_ID: &[u8; 10] = b"pallet-ctf";
+
+/// Storage types as they were before the current layout.
+pub(crate) mod legacy {
+    use super::*;
+
+    /// `ScoreState` as stored up to version 3, holding the bare point total.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+    pub enum ScoreState {
+        Enabled(u64),
+        Disabled,
+    }
+
+    /// The `Score` map from version 2 on, still holding the bare point total.
+    #[frame::deps::frame_support::storage_alias]
+    pub type Score<T: Config> = StorageMap<
+        Pallet<T>,
+        Blake2_128Concat,
+        <T as frame_system::Config>::AccountId,
+        ScoreState,
+        OptionQuery,
+    >;
+}
+
+/// Migrate as many items as `meter` allows, then return where to resume.
+///
+/// Does nothing unless the pallet is at storage version `from`. `migrate_next` migrates the item
+/// after the given cursor and returns its key, or `None` once nothing is left, at which point the
+/// storage version is bumped. Each item is charged `item_weight`.
+fn step_versioned<T: Config, C>(
+    from: u16,
+    mut cursor: Option<C>,
+    meter: &mut WeightMeter,
+    item_weight: Weight,
+    mut migrate_next: impl FnMut(Option<C>) -> Option<C>,
+) -> Result<Option<C>, SteppedMigrationError> {
+    let version_check = T::DbWeight::get().reads(1);
+    let finish = T::DbWeight::get().writes(1);
+    let required = version_check
+        .saturating_add(item_weight)
+        .saturating_add(finish);
+    if !meter.can_consume(required) {
+        return Err(SteppedMigrationError::InsufficientWeight { required });
+    }
+    meter.consume(version_check);
+    if Pallet::<T>::on_chain_storage_version() != from {
+        return Ok(None);
+    }
+    while meter.can_consume(item_weight.saturating_add(finish)) {
+        meter.consume(item_weight);
+        cursor = migrate_next(cursor);
+        if cursor.is_none() {
+            meter.consume(finish);
+            StorageVersion::new(from + 1).put::<Pallet<T>>();
+            return Ok(None);
+        }
+    }
+    Ok(cursor)
+}
+
+/// Migration to storage version 1, which introduces the difficulty histogram.
+pub mod v1 {
+    use super::*;
+
+    mod version_unchecked {
+        use super::*;
+        use crate::DifficultyHistogram;
+
+        /// Initialise the difficulty histogram as empty, no solution has been counted yet.
+        pub struct InitializeHistogram<T>(PhantomData<T>);
+
+        impl<T: Config> UncheckedOnRuntimeUpgrade for InitializeHistogram<T> {
+            fn on_runtime_upgrade() -> Weight {
+                DifficultyHistogram::<T>::put(BoundedVec::default());
+                T::DbWeight::get().writes(1)
+            }
+        }
+    }
+
+    /// Initialise the difficulty histogram when upgrading from storage version 0.
+    pub type MigrateV0ToV1<T> = VersionedMigration<
+        0,
+        1,
+        version_unchecked::InitializeHistogram<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
+/// Migration to storage version 2, which moves `Score` to an iterable hasher.
+pub mod v2 {
+    use super::*;
+    use frame::deps::frame_support::storage_alias;
+    use legacy::ScoreState;
+
+    /// The `Score` map as stored up to version 1, under the non-iterable `Twox128` hasher.
+    mod old {
+        use super::*;
+
+        #[storage_alias]
+        pub type Score<T: Config> = StorageMap<
+            Pallet<T>,
+            Twox128,
+            <T as frame_system::Config>::AccountId,
+            ScoreState,
+            OptionQuery,
+        >;
+    }
+
+    #[cfg(test)]
+    pub(crate) use {legacy::Score as NewScore, old::Score as OldScore};
+
+    /// Re-key `Score` under `Blake2_128Concat` when upgrading from storage version 1.
+    ///
+    /// `Twox128` keys cannot be decoded back into accounts, so the candidates are taken from the
+    /// system account map instead. Scores are only written by signed extrinsics, hence every
+    /// player has an entry there. That map can be arbitrarily large, so the accounts are walked
+    /// over as many blocks as it takes, resuming after the last account visited.
+    pub struct MigrateV1ToV2<T>(PhantomData<T>);
+
+    impl<T: Config> SteppedMigration for MigrateV1ToV2<T> {
+        type Cursor = T::AccountId;
+        type Identifier = MigrationId<10>;
+
+        fn id() -> Self::Identifier {
+            MigrationId {
+                pallet_id: *PALLET_MIGRATIONS_ID,
+                version_from: 1,
+                version_to: 2,
+            }
+        }
+
+        fn step(
+            cursor: Option<Self::Cursor>,
+            meter: &mut WeightMeter,
+        ) -> Result<Option<Self::Cursor>, SteppedMigrationError> {
+            let item_weight = T::DbWeight::get().reads_writes(2, 2);
+            step_versioned::<T, _>(1, cursor, meter, item_weight, |cursor| {
+                let mut accounts = match cursor {
+                    Some(last) => frame_system::Account::<T>::iter_keys_from(
+                        frame_system::Account::<T>::hashed_key_for(last),
+                    ),
+                    None => frame_system::Account::<T>::iter_keys(),
+                };
+                let who = accounts.next()?;
+                if let Some(score) = old::Score::<T>::take(&who) {
+                    legacy::Score::<T>::insert(&who, score);
+                }
+                Some(who)
+            })
+        }
+    }
+}
+
+/// Migration to storage version 3, which introduces the player count.
+pub mod v3 {
+    use super::*;
+    use crate::{PlayerCount, Score};
+
+    /// Count the accounts that already have a score when upgrading from storage version 2.
+    ///
+    /// The count is built up over as many blocks as it takes, resuming after the last account
+    /// counted.
+    pub struct MigrateV2ToV3<T>(PhantomData<T>);
+
+    impl<T: Config> SteppedMigration for MigrateV2ToV3<T> {
+        type Cursor = T::AccountId;
+        type Identifier = MigrationId<10>;
+
+        fn id() -> Self::Identifier {
+            MigrationId {
+                pallet_id: *PALLET_MIGRATIONS_ID,
+                version_from: 2,
+                version_to: 3,
+            }
+        }
+
+        fn step(
+            cursor: Option<Self::Cursor>,
+            meter: &mut WeightMeter,
+        ) -> Result<Option<Self::Cursor>, SteppedMigrationError> {
+            let item_weight = T::DbWeight::get().reads_writes(2, 1);
+            step_versioned::<T, _>(2, cursor, meter, item_weight, |cursor| {
+                let mut players = match cursor {
+                    Some(last) => Score::<T>::iter_keys_from(Score::<T>::hashed_key_for(last)),
+                    None => {
+                        PlayerCount::<T>::kill();
+                        Score::<T>::iter_keys()
+                    }
+                };
+                let who = players.next()?;
+                PlayerCount::<T>::mutate(|count| *count = count.saturating_add(1));
+                Some(who)
+            })
+        }
+    }
+}
+
+/// Migration to storage version 4, which replaces the bare point total with `PlayerStats`.
+pub mod v4 {
+    use super::*;
+    use crate::{PlayerStats, Score, ScoreState};
+
+    /// Translate `Score` to `PlayerStats` when upgrading from storage version 3.
+    ///
+    /// Every point total is carried over into stats without a submission history, over as many
+    /// blocks as it takes, resuming after the last account translated.
+    pub struct MigrateV3ToV4<T>(PhantomData<T>);
+
+    impl<T: Config> SteppedMigration for MigrateV3ToV4<T> {
+        type Cursor = T::AccountId;
+        type Identifier = MigrationId<10>;
+
+        fn id() -> Self::Identifier {
+            MigrationId {
+                pallet_id: *PALLET_MIGRATIONS_ID,
+                version_from: 3,
+                version_to: 4,
+            }
+        }
+
+        fn step(
+            cursor: Option<Self::Cursor>,
+            meter: &mut WeightMeter,
+        ) -> Result<Option<Self::Cursor>, SteppedMigrationError> {
+            let item_weight = T::DbWeight::get().reads_writes(1, 1);
+            step_versioned::<T, _>(3, cursor, meter, item_weight, |cursor| {
+                let mut scores = match cursor {
+                    Some(last) => {
+                        legacy::Score::<T>::iter_from(legacy::Score::<T>::hashed_key_for(last))
+                    }
+                    None => legacy::Score::<T>::iter(),
+                };
+                let (who, old) = scores.next()?;
+                let new = match old {
+                    legacy::ScoreState::Enabled(points) => {
+                        ScoreState::Enabled(PlayerStats::with_points(points))
+                    }
+                    legacy::ScoreState::Disabled => ScoreState::Disabled,
+                };
+                Score::<T>::insert(&who, new);
+                Some(who)
+            })
+        }
+    }
+}
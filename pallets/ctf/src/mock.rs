@@ -0,0 +1,224 @@
+use frame::{
+    deps::{frame_support::weights::constants::RocksDbWeight, frame_system::GenesisConfig},
+    hashing::{blake2_256, H256, U256},
+    prelude::*,
+    runtime::prelude::*,
+    testing_prelude::*,
+    traits::Randomness,
+};
+
+// Configure a mock runtime to test the pallet.
+#[frame_construct_runtime]
+mod test_runtime {
+    #[runtime::runtime]
+    #[runtime::derive(
+        RuntimeCall,
+        RuntimeEvent,
+        RuntimeError,
+        RuntimeOrigin,
+        RuntimeFreezeReason,
+        RuntimeHoldReason,
+        RuntimeSlashReason,
+        RuntimeLockId,
+        RuntimeTask
+    )]
+    pub struct Test;
+
+    #[runtime::pallet_index(0)]
+    pub type System = frame_system;
+    #[runtime::pallet_index(1)]
+    pub type Ctf = crate;
+    #[runtime::pallet_index(2)]
+    pub type Balances = pallet_balances;
+}
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig)]
+impl frame_system::Config for Test {
+    type Nonce = u64;
+    type Block = MockBlock<Test>;
+    type BlockHashCount = ConstU64<250>;
+    type DbWeight = RocksDbWeight;
+    type AccountData = pallet_balances::AccountData<u64>;
+}
+
+#[derive_impl(pallet_balances::config_preludes::TestDefaultConfig)]
+impl pallet_balances::Config for Test {
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+}
+
+parameter_types! {
+    pub static ExistentialDeposit: u64 = 1;
+    pub static LotteryEntryCost: u64 = 0;
+    pub static PowRounds: u32 = 1;
+    pub static SubmissionCooldown: u64 = 0;
+    pub static WithdrawCooldown: u64 = 0;
+    pub static SubmissionQuota: u32 = 0;
+    pub static MaxTotalSubmissions: u64 = 0;
+    pub static QuotaPeriod: u64 = 10;
+    pub static EscrowPeriod: u64 = 0;
+    pub static PointsGranularity: u64 = 1;
+    pub static BurstDecay: Percent = Percent::from_percent(100);
+    pub static MinScoreForLottery: u64 = 0;
+    pub static LotteryThreshold: u32 = 20;
+    pub static MinLotteryEntrants: u32 = 1;
+    pub static MaxRandomnessAge: u64 = 0;
+    pub static TransferToDisabled: crate::DisabledRecipientPolicy =
+        crate::DisabledRecipientPolicy::Reject;
+    pub static AllowUnsignedLottery: bool = true;
+    pub static RolloverPrize: bool = false;
+    pub static RewardPerPoint: u64 = 0;
+    pub static MaxTotalScore: u64 = u64::MAX / 2;
+    pub static LifetimeScoreCap: u64 = 0;
+    pub static TierThresholds: [u64; 4] = [100, 1000, 10000, 100000];
+    pub static LotteryStake: u64 = 0;
+    pub static VerboseVerification: bool = false;
+    pub static AllowImport: bool = true;
+    pub static RandomnessOverride: Option<H256> = None;
+    pub static RandomnessKnownSince: Option<u64> = None;
+    pub Milestones: BoundedVec<u64, ConstU32<16>> = BoundedVec::truncate_from(vec![1000, 10000]);
+    pub Tiers: BoundedVec<(u32, BoundedVec<u8, ConstU32<16>>), ConstU32<8>> =
+        BoundedVec::truncate_from(vec![
+            (4, BoundedVec::truncate_from(b"Bronze".to_vec())),
+            (8, BoundedVec::truncate_from(b"Silver".to_vec())),
+            (12, BoundedVec::truncate_from(b"Gold".to_vec())),
+        ]);
+}
+
+impl crate::Config for Test {
+    type RuntimeEvent = RuntimeEvent;
+    type MinDifficulty = ConstU32<1>;
+    type MaxDifficulty = ConstU32<64>;
+    type PowRounds = PowRounds;
+    type PointsGranularity = PointsGranularity;
+    type BurstDecay = BurstDecay;
+    type Tiers = Tiers;
+    type MaxTotalScore = MaxTotalScore;
+    type LifetimeScoreCap = LifetimeScoreCap;
+    type Milestones = Milestones;
+    type TierThresholds = TierThresholds;
+    type LeaderboardSize = ConstU32<3>;
+    type MinScoreForLottery = MinScoreForLottery;
+    type LotteryThreshold = LotteryThreshold;
+    type MinLotteryEntrants = MinLotteryEntrants;
+    type MaxRandomnessAge = MaxRandomnessAge;
+    type LotteryDifficulty = ConstU32<4>;
+    type AllowUnsignedLottery = AllowUnsignedLottery;
+    type LotteryEntryCost = LotteryEntryCost;
+    type LotteryStake = LotteryStake;
+    type RolloverPrize = RolloverPrize;
+    type NonceTolerance = ConstU32<1>;
+    type SubmissionCooldown = SubmissionCooldown;
+    type WithdrawCooldown = WithdrawCooldown;
+    type MaxTotalSubmissions = MaxTotalSubmissions;
+    type SubmissionQuota = SubmissionQuota;
+    type QuotaPeriod = QuotaPeriod;
+    type EscrowPeriod = EscrowPeriod;
+    type Randomness = TestRandomness;
+    type AuthorBonus = ConstU64<10>;
+    type Currency = Balances;
+    type RewardPerPoint = RewardPerPoint;
+    type TransferToDisabled = TransferToDisabled;
+    type SubmissionFilter = TestSubmissionFilter;
+    type VerboseVerification = VerboseVerification;
+    type AllowImport = AllowImport;
+    type AdminOrigin = EnsureRoot<u64>;
+    type WeightInfo = ();
+}
+
+/// Randomness returning `RandomnessOverride` when set, the pallet's own chain otherwise, as
+/// determined at `RandomnessKnownSince` when set and the current block otherwise.
+pub struct TestRandomness;
+
+impl Randomness<H256, u64> for TestRandomness {
+    fn random(subject: &[u8]) -> (H256, u64) {
+        let (output, block) = match RandomnessOverride::get() {
+            Some(seed) => (seed, System::block_number()),
+            None => crate::SelfChainedRandomness::<Test>::random(subject),
+        };
+        (output, RandomnessKnownSince::get().unwrap_or(block))
+    }
+}
+
+/// Anti-cheat stand-in vetoing every solution above difficulty 50.
+pub struct TestSubmissionFilter;
+
+impl crate::SubmissionFilter<u64> for TestSubmissionFilter {
+    fn allow(_who: &u64, difficulty: u32) -> bool {
+        difficulty <= 50
+    }
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> TestState {
+    build_test_ext(None)
+}
+
+// Build genesis storage with the lottery randomness pinned to `seed`.
+pub fn new_test_ext_with_randomness(seed: H256) -> TestState {
+    build_test_ext(Some(seed))
+}
+
+fn build_test_ext(lottery_randomness: Option<H256>) -> TestState {
+    let mut storage = GenesisConfig::<Test>::default().build_storage().unwrap();
+    crate::GenesisConfig::<Test> {
+        lottery_randomness,
+        ..Default::default()
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+
+    let mut ext: TestState = storage.into();
+    // Events are not deposited on the genesis block.
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
+
+/// Find a `work` value satisfying the proof-of-work for `who` at its current nonce.
+pub fn mine(who: u64, difficulty: u32) -> H256 {
+    Ctf::mine(&who, System::account_nonce(who) as u32, difficulty)
+}
+
+/// Find a `work` value for `who` at its current nonce whose hash of `difficulty` is below
+/// `target`.
+pub fn mine_below(who: u64, difficulty: u32, target: U256) -> H256 {
+    let nonce = System::account_nonce(who) as u32;
+    (0u64..)
+        .map(H256::from_low_u64_be)
+        .find(|work| {
+            let mut hash = blake2_256(&Ctf::pow_preimage(&who, nonce, difficulty, work));
+            for _ in 1..Ctf::pow_rounds() {
+                hash = blake2_256(&hash);
+            }
+            U256::from_little_endian(&hash) < target
+        })
+        .expect("a low difficulty proof is always found")
+}
+
+/// Fill the lottery with `count` entries for accounts starting at `first`, bypassing the PoW.
+pub fn fill_lottery(first: u64, count: u32) {
+    for who in first..first + count as u64 {
+        crate::LotteryEntries::<Test>::insert(who, 0);
+    }
+    crate::LotteryEntryCount::<Test>::mutate(|c| *c += count);
+}
+
+/// Advance to the next block, running the pallet's `on_initialize`.
+pub fn next_block() {
+    let n = System::block_number() + 1;
+    System::set_block_number(n);
+    Ctf::on_initialize(n);
+}
+
+/// An enabled score holding `points` and no submission history.
+pub fn enabled(points: u64) -> crate::ScoreState {
+    crate::ScoreState::Enabled(crate::PlayerStats::with_points(points))
+}
+
+/// The points of `who`, panicking if the account has withdrawn.
+pub fn points(who: u64) -> u64 {
+    match crate::Score::<Test>::get(who) {
+        crate::ScoreState::Enabled(stats) => stats.points,
+        crate::ScoreState::Disabled => panic!("account {who} has withdrawn"),
+    }
+}
@@ -0,0 +1,81 @@
+//! Runtime API definition for the CTF pallet.
+
+use crate::{BadgeTier, ScorePage};
+use codec::Codec;
+use sp_runtime::DispatchError;
+use sp_std::vec::Vec;
+
+frame::runtime::apis::decl_runtime_apis! {
+    /// Read-only queries into the CTF game state.
+    pub trait ChallengeApi<AccountId, BlockNumber, Hash>
+    where
+        AccountId: Codec,
+        BlockNumber: Codec,
+        Hash: Codec,
+    {
+        /// The most recent lottery winners as `(winner, points_awarded, block)`, oldest first.
+        fn recent_winners() -> Vec<(AccountId, u64, BlockNumber)>;
+
+        /// The number of accepted solutions per difficulty as `(difficulty, count)`.
+        fn get_histogram() -> Vec<(u32, u32)>;
+
+        /// The points of `account` and the block they were read at, `None` for accounts that
+        /// never played or have withdrawn. Read-only and unsigned: signing the attestation with
+        /// a node key is left to the layer calling this API.
+        fn score_attestation(account: AccountId) -> Option<(u64, BlockNumber)>;
+
+        /// The highest tier `account` has reached, `None` below Bronze.
+        fn get_tier(account: AccountId) -> Option<BadgeTier>;
+
+        /// The enabled account with the most points, the lowest account id on a tie.
+        fn leader() -> Option<(AccountId, u64)>;
+
+        /// The top `n` leaderboard entries as `(raw account bytes, score)`, highest first.
+        fn leaderboard_raw(n: u32) -> Vec<([u8; 32], u64)>;
+
+        /// Up to `limit` stored scores, withdrawn ones included, following the account `start`.
+        /// Pass the returned cursor as the next `start`, it is `None` once every score is
+        /// exported.
+        fn export_scores(start: Option<AccountId>, limit: u32) -> ScorePage<AccountId>;
+
+        /// The most recent proof verifications as `(who, difficulty, valid, block)`, oldest first.
+        fn verification_log() -> Vec<(AccountId, u32, bool, BlockNumber)>;
+
+        /// The total points given up through withdrawals.
+        fn total_withdrawn() -> u128;
+
+        /// The total points burned: sent to withdrawn accounts, cut off by `MaxTotalScore` or
+        /// escrowed for accounts that withdrew before their release.
+        fn total_burned() -> u128;
+
+        /// Print the score state of `account` to the node log, visible with `RUST_LOG=runtime`
+        /// at debug level. Returns nothing, it exists for operators debugging a deployment.
+        fn debug_print_score(account: AccountId);
+
+        /// The number of accounts that have a score.
+        fn player_count() -> u32;
+
+        /// The number of lottery entries still needed before the next draw, zero once reached.
+        fn lottery_draw_countdown() -> u32;
+
+        /// Whether each candidate `work` value is a valid proof for `account` at `nonce` and
+        /// `difficulty`, checking at most 100 candidates per call.
+        fn verify_batch_pow(
+            account: AccountId,
+            nonce: u32,
+            difficulty: u32,
+            candidates: Vec<Hash>,
+        ) -> Result<Vec<bool>, DispatchError>;
+
+        /// The exact bytes hashed with blake2-256 to check `work` for `account` at `nonce` and
+        /// `difficulty` under the current proof-of-work version.
+        fn pow_preimage(account: AccountId, nonce: u32, difficulty: u32, work: Hash) -> Vec<u8>;
+
+        /// Times blake2-256 is applied, starting from the preimage, before the digest is compared
+        /// with the target.
+        fn pow_rounds() -> u32;
+
+        /// The semantic version of the pallet as `(major, minor, patch)`.
+        fn pallet_version() -> (u8, u8, u8);
+    }
+}
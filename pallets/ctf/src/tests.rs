@@ -0,0 +1,2650 @@
+use crate::{
+    leaderboard::{remove_entry, sorted_insert_or_update, BoundedLeaderboard},
+    migrations,
+    migrations::legacy::ScoreState as LegacyScoreState,
+    mock::*,
+    BadgeTier, DifficultyHistogram, DisabledRecipientPolicy, Error, EscrowReleases, EscrowedScore,
+    Event, LastPowNonce, Leaderboard, LifetimeEarned, LotteryBonusPool, LotteryEntries,
+    LotteryEntryCount, LotteryNoWinnerReason, LotteryNonce, LotteryRandomness, NextLotteryBlock,
+    OperatorWhitelist, PlayerStats, PowVersion, PowVersionGrace, RandomnessUpdatedAt,
+    ReachedMilestones, Score, ScoreState, SubmissionHints, UsedNonces, WinnerHistory,
+    WithdrawCooldownUntil,
+};
+use frame::{
+    deps::frame_support::{
+        migrations::{SteppedMigration, SteppedMigrationError},
+        weights::WeightMeter,
+    },
+    hashing::{blake2_256, H256, U256},
+    testing_prelude::*,
+};
+
+#[test]
+fn lottery_draw_records_winner() {
+    new_test_ext().execute_with(|| {
+        fill_lottery(1, 20);
+        next_block();
+
+        assert_eq!(LotteryEntryCount::<Test>::get(), 0);
+        // Without randomness the first entry in iteration order wins.
+        let history = Ctf::recent_winners();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].1, 800);
+        assert_eq!(history[0].2, 2);
+        assert_eq!(Ctf::leaderboard(), vec![(history[0].0, 800)]);
+
+        // The winner's entry is closed too, so they can enter the next round
+        assert_eq!(LotteryEntries::<Test>::iter_keys().count(), 0);
+        let winner = history[0].0;
+        let work = mine(winner, 4);
+        assert_ok!(Ctf::enter_lottery(RuntimeOrigin::signed(winner), work));
+    });
+}
+
+#[test]
+fn winner_history_keeps_latest_fifty_in_order() {
+    new_test_ext().execute_with(|| {
+        for _ in 0..55 {
+            fill_lottery(1, 20);
+            next_block();
+        }
+
+        let history = WinnerHistory::<Test>::get();
+        assert_eq!(history.len(), 50);
+        // Draws happened at blocks 2..=56, the first five were evicted.
+        let blocks: Vec<u64> = history.iter().map(|(_, _, block)| *block).collect();
+        assert_eq!(blocks, (7..=56).collect::<Vec<u64>>());
+    });
+}
+
+#[test]
+fn lottery_entry_cost_is_paid_into_bonus_pool() {
+    new_test_ext().execute_with(|| {
+        LotteryEntryCost::set(30);
+        Score::<Test>::insert(1, enabled(100));
+
+        let work = mine(1, 4);
+        assert_ok!(Ctf::enter_lottery(RuntimeOrigin::signed(1), work));
+
+        assert_eq!(Score::<Test>::get(1), enabled(70));
+        assert_eq!(LotteryBonusPool::<Test>::get(), 30);
+        assert!(LotteryEntries::<Test>::contains_key(1));
+    });
+}
+
+#[test]
+fn leave_lottery_releases_the_stake_and_allows_reentry() {
+    new_test_ext().execute_with(|| {
+        LotteryStake::set(10);
+        LotteryEntryCost::set(30);
+        assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), 1, 100));
+        Score::<Test>::insert(1, enabled(100));
+
+        assert_noop!(
+            Ctf::leave_lottery(RuntimeOrigin::signed(1)),
+            Error::<Test>::NotInLottery
+        );
+
+        let work = mine(1, 4);
+        assert_ok!(Ctf::enter_lottery(RuntimeOrigin::signed(1), work));
+        assert_eq!(Balances::reserved_balance(1), 10);
+
+        assert_ok!(Ctf::leave_lottery(RuntimeOrigin::signed(1)));
+        System::assert_last_event(
+            Event::LotteryEntryRemoved {
+                who: 1,
+                new_count: 0,
+            }
+            .into(),
+        );
+        assert!(!LotteryEntries::<Test>::contains_key(1));
+        assert_eq!(LotteryEntryCount::<Test>::get(), 0);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        // The entry cost is not refunded
+        assert_eq!(LotteryBonusPool::<Test>::get(), 30);
+
+        // Entering again pays the cost and proof-of-work again
+        System::inc_account_nonce(1);
+        let work = mine(1, 4);
+        assert_ok!(Ctf::enter_lottery(RuntimeOrigin::signed(1), work));
+        assert_eq!(Score::<Test>::get(1), enabled(40));
+        assert_eq!(LotteryEntryCount::<Test>::get(), 1);
+    });
+}
+
+#[test]
+fn lottery_entry_cost_must_be_affordable() {
+    new_test_ext().execute_with(|| {
+        LotteryEntryCost::set(30);
+        Score::<Test>::insert(1, enabled(10));
+
+        let work = mine(1, 4);
+        assert_noop!(
+            Ctf::enter_lottery(RuntimeOrigin::signed(1), work),
+            Error::<Test>::InsufficientScore
+        );
+    });
+}
+
+#[test]
+fn lottery_winner_collects_bonus_pool() {
+    new_test_ext().execute_with(|| {
+        LotteryBonusPool::<Test>::put(60);
+        fill_lottery(1, 20);
+        next_block();
+
+        assert_eq!(LotteryBonusPool::<Test>::get(), 0);
+        assert_eq!(Ctf::recent_winners()[0].1, 860);
+    });
+}
+
+#[test]
+fn lottery_draw_recounts_drifted_entries() {
+    new_test_ext().execute_with(|| {
+        // 25 entrants while the stored count only knows about 20 of them.
+        fill_lottery(1, 20);
+        for who in 21..=25 {
+            LotteryEntries::<Test>::insert(who, 0);
+        }
+        next_block();
+
+        assert_eq!(Ctf::recent_winners().len(), 1);
+        // Every entry was closed and the count was reset after the draw.
+        assert_eq!(LotteryEntries::<Test>::iter_keys().count(), 0);
+        assert_eq!(LotteryEntryCount::<Test>::get(), 0);
+    });
+}
+
+#[test]
+fn ensure_admin_accepts_only_admin_origin() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Ctf::ensure_admin(RuntimeOrigin::root()));
+        assert_noop!(Ctf::ensure_admin(RuntimeOrigin::signed(1)), BadOrigin);
+        assert_noop!(Ctf::ensure_admin(RuntimeOrigin::none()), BadOrigin);
+    });
+}
+
+#[test]
+fn migration_to_v1_initializes_histogram() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(0).put::<Ctf>();
+        assert!(!DifficultyHistogram::<Test>::exists());
+
+        migrations::v1::MigrateV0ToV1::<Test>::on_runtime_upgrade();
+
+        assert!(DifficultyHistogram::<Test>::exists());
+        assert_eq!(Ctf::on_chain_storage_version(), 1);
+    });
+}
+
+/// Step `M` to completion with a fresh meter each step, returning the number of steps taken.
+fn run_stepped_migration<M: SteppedMigration>() -> u32 {
+    let mut cursor = None;
+    let mut steps = 0;
+    loop {
+        steps += 1;
+        cursor = M::step(cursor, &mut WeightMeter::with_limit(Weight::MAX)).unwrap();
+        if cursor.is_none() {
+            return steps;
+        }
+    }
+}
+
+#[test]
+fn migration_to_v2_rehashes_scores() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(1).put::<Ctf>();
+        for who in 1..=3 {
+            System::inc_account_nonce(who);
+            migrations::v2::OldScore::<Test>::insert(who, LegacyScoreState::Enabled(who * 10));
+        }
+        migrations::v2::OldScore::<Test>::insert(3, LegacyScoreState::Disabled);
+        assert_eq!(migrations::v2::NewScore::<Test>::iter().count(), 0);
+
+        run_stepped_migration::<migrations::v2::MigrateV1ToV2<Test>>();
+
+        let mut scores: Vec<_> = migrations::v2::NewScore::<Test>::iter().collect();
+        scores.sort_by_key(|(who, _)| *who);
+        assert_eq!(
+            scores,
+            vec![
+                (1, LegacyScoreState::Enabled(10)),
+                (2, LegacyScoreState::Enabled(20)),
+                (3, LegacyScoreState::Disabled),
+            ]
+        );
+        assert!(!migrations::v2::OldScore::<Test>::contains_key(1));
+        assert_eq!(Ctf::on_chain_storage_version(), 2);
+    });
+}
+
+#[test]
+fn migration_to_v2_resumes_across_blocks() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(1).put::<Ctf>();
+        for who in 1..=3 {
+            System::inc_account_nonce(who);
+            migrations::v2::OldScore::<Test>::insert(who, LegacyScoreState::Enabled(who * 10));
+        }
+        let db = <Test as frame_system::Config>::DbWeight::get();
+        let item = db.reads_writes(2, 2);
+        let overhead = db.reads(1).saturating_add(db.writes(1));
+
+        // Too little weight for even a single account is reported rather than skipped.
+        let mut meter = WeightMeter::with_limit(item);
+        assert!(matches!(
+            migrations::v2::MigrateV1ToV2::<Test>::step(None, &mut meter),
+            Err(SteppedMigrationError::InsufficientWeight { .. })
+        ));
+
+        // Each step covers as many accounts as its meter allows and no more.
+        let mut cursor = None;
+        let mut steps = 0;
+        loop {
+            let mut meter = WeightMeter::with_limit(overhead.saturating_add(item));
+            cursor = migrations::v2::MigrateV1ToV2::<Test>::step(cursor, &mut meter).unwrap();
+            assert!(meter.consumed().all_lte(meter.limit()));
+            steps += 1;
+            if cursor.is_none() {
+                break;
+            }
+            assert_eq!(Ctf::on_chain_storage_version(), 1);
+        }
+
+        // One step per account, plus one to find the end of the map.
+        assert_eq!(steps, 4);
+        assert_eq!(migrations::v2::NewScore::<Test>::iter().count(), 3);
+        assert_eq!(migrations::v2::OldScore::<Test>::get(2), None);
+        assert_eq!(Ctf::on_chain_storage_version(), 2);
+    });
+}
+
+#[test]
+fn stepped_migrations_skip_other_storage_versions() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(4).put::<Ctf>();
+        Score::<Test>::insert(1, enabled(10));
+
+        assert_eq!(
+            run_stepped_migration::<migrations::v3::MigrateV2ToV3<Test>>(),
+            1
+        );
+
+        assert_eq!(Ctf::player_count(), 0);
+        assert_eq!(Ctf::on_chain_storage_version(), 4);
+    });
+}
+
+#[test]
+fn migration_to_v3_counts_existing_players() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(2).put::<Ctf>();
+        Score::<Test>::insert(1, enabled(10));
+        Score::<Test>::insert(2, ScoreState::Disabled);
+
+        run_stepped_migration::<migrations::v3::MigrateV2ToV3<Test>>();
+
+        assert_eq!(Ctf::player_count(), 2);
+        assert_eq!(Ctf::on_chain_storage_version(), 3);
+    });
+}
+
+#[test]
+fn migration_to_v4_wraps_points_in_player_stats() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(3).put::<Ctf>();
+        migrations::v2::NewScore::<Test>::insert(1, LegacyScoreState::Enabled(10));
+        migrations::v2::NewScore::<Test>::insert(2, LegacyScoreState::Disabled);
+
+        run_stepped_migration::<migrations::v4::MigrateV3ToV4<Test>>();
+
+        assert_eq!(Score::<Test>::get(1), enabled(10));
+        assert_eq!(Score::<Test>::get(2), ScoreState::Disabled);
+        assert_eq!(Ctf::on_chain_storage_version(), 4);
+    });
+}
+
+#[test]
+fn withdraw_by_unknown_account_fails() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Ctf::withdraw(RuntimeOrigin::signed(1)),
+            Error::<Test>::NeverPlayed
+        );
+        assert!(!Score::<Test>::contains_key(1));
+    });
+}
+
+#[test]
+fn withdraw_with_zero_score_disables_account() {
+    new_test_ext().execute_with(|| {
+        Score::<Test>::insert(1, enabled(0));
+
+        assert_ok!(Ctf::withdraw(RuntimeOrigin::signed(1)));
+
+        assert_eq!(Score::<Test>::get(1), ScoreState::Disabled);
+        System::assert_last_event(
+            Event::Withdrawn {
+                who: 1,
+                points: 0,
+                rank: None,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn pinned_randomness_selects_expected_winner() {
+    let seed = H256::repeat_byte(7);
+    new_test_ext_with_randomness(seed).execute_with(|| {
+        assert_eq!(LotteryRandomness::<Test>::get(), Some(seed));
+        fill_lottery(1, 20);
+        let entrants: Vec<u64> = LotteryEntries::<Test>::iter_keys().collect();
+
+        next_block();
+
+        // `on_initialize` rotates the seed with the block number before drawing.
+        let rotated = blake2_256(&(seed, 2u64).encode());
+        let index = u32::from_be_bytes([rotated[0], rotated[1], rotated[2], rotated[3]]) % 20;
+        assert_eq!(index, 11);
+        assert_eq!(Ctf::recent_winners()[0].0, entrants[index as usize]);
+    });
+}
+
+#[test]
+fn lottery_stakes_are_returned_to_losers_and_slashed_from_the_winner() {
+    new_test_ext().execute_with(|| {
+        LotteryStake::set(10);
+        LotteryThreshold::set(3);
+        for who in 1..=4 {
+            assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), who, 100));
+        }
+        assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), 4, 5));
+
+        let work = mine(4, 4);
+        assert_noop!(
+            Ctf::enter_lottery(RuntimeOrigin::signed(4), work),
+            pallet_balances::Error::<Test>::InsufficientBalance
+        );
+        for who in 1..=3 {
+            let work = mine(who, 4);
+            assert_ok!(Ctf::enter_lottery(RuntimeOrigin::signed(who), work));
+            assert_eq!(Balances::reserved_balance(who), 10);
+        }
+
+        next_block();
+        let winner = Ctf::recent_winners()[0].0;
+        for who in 1..=3 {
+            assert_eq!(Balances::reserved_balance(who), 0);
+            let expected = if who == winner { 90 } else { 100 };
+            assert_eq!(Balances::free_balance(who), expected);
+        }
+        assert_eq!(Balances::total_issuance(), 295);
+    });
+}
+
+#[test]
+fn lottery_only_releases_the_stake_it_reserved() {
+    new_test_ext().execute_with(|| {
+        LotteryStake::set(10);
+        LotteryThreshold::set(2);
+        for who in 1..=2 {
+            assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), who, 100));
+            // Held by something other than the lottery
+            assert_ok!(<Balances as frame::traits::ReservableCurrency<u64>>::reserve(&who, 50));
+        }
+
+        for who in 1..=2 {
+            let work = mine(who, 4);
+            assert_ok!(Ctf::enter_lottery(RuntimeOrigin::signed(who), work));
+        }
+        // A stake raised after entering does not change what the entries hold
+        LotteryStake::set(30);
+        next_block();
+
+        let winner = Ctf::recent_winners()[0].0;
+        let loser = if winner == 1 { 2 } else { 1 };
+        assert_eq!(Balances::reserved_balance(winner), 50);
+        assert_eq!(Balances::reserved_balance(loser), 50);
+
+        // The winner's entry is gone, leaving cannot release the other reserve
+        assert_noop!(
+            Ctf::leave_lottery(RuntimeOrigin::signed(winner)),
+            Error::<Test>::NotInLottery
+        );
+        assert_eq!(Balances::reserved_balance(winner), 50);
+    });
+}
+
+#[test]
+fn reaching_the_lottery_threshold_announces_the_draw() {
+    new_test_ext().execute_with(|| {
+        LotteryThreshold::set(2);
+        let reached = || {
+            System::events()
+                .into_iter()
+                .filter(|record| {
+                    matches!(
+                        record.event,
+                        RuntimeEvent::Ctf(Event::LotteryThresholdReached { .. })
+                    )
+                })
+                .count()
+        };
+
+        let work = mine(1, 4);
+        assert_ok!(Ctf::enter_lottery(RuntimeOrigin::signed(1), work));
+        assert_eq!(reached(), 0);
+
+        let work = mine(2, 4);
+        assert_ok!(Ctf::enter_lottery(RuntimeOrigin::signed(2), work));
+        System::assert_last_event(Event::LotteryThresholdReached { entry_count: 2 }.into());
+
+        // Entries past the threshold do not announce the draw again
+        let work = mine(3, 4);
+        assert_ok!(Ctf::enter_lottery(RuntimeOrigin::signed(3), work));
+        assert_eq!(reached(), 1);
+    });
+}
+
+#[test]
+fn whitelist_mode_restricts_players_to_whitelist() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Ctf::set_whitelist_mode(RuntimeOrigin::root(), true));
+        assert_ok!(Ctf::add_to_whitelist(RuntimeOrigin::root(), 1));
+        assert!(OperatorWhitelist::<Test>::contains_key(1));
+
+        let work = mine(2, 4);
+        assert_noop!(
+            Ctf::enter_lottery(RuntimeOrigin::signed(2), work),
+            Error::<Test>::NotWhitelisted
+        );
+        let work = mine(1, 4);
+        assert_ok!(Ctf::enter_lottery(RuntimeOrigin::signed(1), work));
+
+        // After the public launch everyone may play again.
+        assert_ok!(Ctf::set_whitelist_mode(RuntimeOrigin::root(), false));
+        let work = mine(2, 4);
+        assert_ok!(Ctf::enter_lottery(RuntimeOrigin::signed(2), work));
+    });
+}
+
+#[test]
+fn whitelist_is_managed_by_admin_only() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Ctf::add_to_whitelist(RuntimeOrigin::signed(1), 1),
+            BadOrigin
+        );
+        assert_noop!(
+            Ctf::set_whitelist_mode(RuntimeOrigin::signed(1), true),
+            BadOrigin
+        );
+
+        assert_ok!(Ctf::add_to_whitelist(RuntimeOrigin::root(), 1));
+        assert_ok!(Ctf::remove_from_whitelist(RuntimeOrigin::root(), 1));
+        assert!(!OperatorWhitelist::<Test>::contains_key(1));
+        System::assert_last_event(Event::RemovedFromWhitelist { who: 1 }.into());
+    });
+}
+
+#[test]
+fn crossing_a_milestone_is_announced_once() {
+    new_test_ext().execute_with(|| {
+        let milestone_events = || {
+            System::events()
+                .into_iter()
+                .filter(|record| {
+                    matches!(
+                        record.event,
+                        RuntimeEvent::Ctf(Event::MilestoneReached { .. })
+                    )
+                })
+                .count()
+        };
+
+        // Difficulty 10 is worth 512 points, below the first milestone.
+        let work = mine(1, 10);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 10, work));
+        assert_eq!(milestone_events(), 0);
+
+        System::inc_account_nonce(1);
+        let work = mine(1, 10);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 10, work));
+        System::assert_has_event(
+            Event::MilestoneReached {
+                who: 1,
+                milestone: 1000,
+            }
+            .into(),
+        );
+        assert_eq!(milestone_events(), 1);
+        assert_eq!(ReachedMilestones::<Test>::get(1), 0b1);
+
+        // Further solutions above 1000 do not announce it again.
+        System::inc_account_nonce(1);
+        let work = mine(1, 1);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 1, work));
+        assert_eq!(milestone_events(), 1);
+    });
+}
+
+#[test]
+fn version_matches_crate_version() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(Ctf::version(), crate::PALLET_VERSION);
+        let crate_version = Ctf::crate_version();
+        assert_eq!(
+            Ctf::version(),
+            (
+                crate_version.major as u8,
+                crate_version.minor,
+                crate_version.patch
+            )
+        );
+    });
+}
+
+#[test]
+fn config_values_are_exposed_as_constants() {
+    assert_eq!(
+        <<Test as crate::Config>::MinDifficulty as Get<u32>>::get(),
+        1
+    );
+    assert_eq!(
+        <<Test as crate::Config>::MaxDifficulty as Get<u32>>::get(),
+        64
+    );
+    assert_eq!(
+        <<Test as crate::Config>::LotteryThreshold as Get<u32>>::get(),
+        20
+    );
+    assert_eq!(
+        <<Test as crate::Config>::LotteryDifficulty as Get<u32>>::get(),
+        4
+    );
+
+    let constants: Vec<_> = Ctf::pallet_constants_metadata()
+        .into_iter()
+        .map(|constant| constant.name)
+        .collect();
+    for name in [
+        "MinDifficulty",
+        "MaxDifficulty",
+        "Milestones",
+        "MinScoreForLottery",
+        "LotteryThreshold",
+        "LotteryDifficulty",
+        "LotteryEntryCost",
+        "SubmissionCooldown",
+    ] {
+        assert!(constants.contains(&name), "{name} is not in the metadata");
+    }
+}
+
+#[test]
+fn redeeming_a_code_credits_its_value() {
+    new_test_ext().execute_with(|| {
+        let code = b"flag{hybrid}".to_vec();
+        assert_ok!(Ctf::create_codes(
+            RuntimeOrigin::root(),
+            vec![(H256(blake2_256(&code)), 250)]
+        ));
+
+        assert_ok!(Ctf::redeem(
+            RuntimeOrigin::signed(1),
+            code.try_into().unwrap()
+        ));
+
+        assert_eq!(Score::<Test>::get(1), enabled(250));
+        assert_eq!(Ctf::leaderboard(), vec![(1, 250)]);
+        System::assert_last_event(Event::CodeRedeemed { who: 1, value: 250 }.into());
+    });
+}
+
+#[test]
+fn code_cannot_be_redeemed_twice() {
+    new_test_ext().execute_with(|| {
+        let code: BoundedVec<u8, ConstU32<64>> = b"flag{once}".to_vec().try_into().unwrap();
+        assert_ok!(Ctf::create_codes(
+            RuntimeOrigin::root(),
+            vec![(H256(blake2_256(&code)), 100)]
+        ));
+        assert_ok!(Ctf::redeem(RuntimeOrigin::signed(1), code.clone()));
+
+        assert_noop!(
+            Ctf::redeem(RuntimeOrigin::signed(2), code),
+            Error::<Test>::InvalidCode
+        );
+        assert_eq!(Score::<Test>::get(1), enabled(100));
+    });
+}
+
+#[test]
+fn dry_run_reports_proof_validity_without_scoring() {
+    new_test_ext().execute_with(|| {
+        let work = mine(1, 8);
+        assert_ok!(Ctf::verify_pow_dry_run(RuntimeOrigin::signed(1), 8, work));
+        System::assert_last_event(
+            Event::ProofVerified {
+                who: 1,
+                difficulty: 8,
+                valid: true,
+            }
+            .into(),
+        );
+        assert!(!Score::<Test>::contains_key(1));
+        assert!(Ctf::histogram().is_empty());
+
+        // The same proof is still accepted by the scoring call afterwards.
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 8, work));
+        assert_eq!(points(1), 128);
+    });
+}
+
+#[test]
+fn paused_pallet_rejects_calls_until_unpaused() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(Ctf::pause_pallet(RuntimeOrigin::signed(1)), BadOrigin);
+        assert_ok!(Ctf::pause_pallet(RuntimeOrigin::root()));
+
+        let work = mine(1, 4);
+        assert_noop!(
+            Ctf::enter_lottery(RuntimeOrigin::signed(1), work),
+            Error::<Test>::PalletPaused
+        );
+        assert_noop!(
+            Ctf::set_whitelist_mode(RuntimeOrigin::root(), true),
+            Error::<Test>::PalletPaused
+        );
+        // The pause is checked before the origin and arguments of every submission
+        assert_noop!(
+            Ctf::submit_solution_with_hint(RuntimeOrigin::none(), 4, work, Default::default()),
+            Error::<Test>::PalletPaused
+        );
+        assert_noop!(
+            Ctf::submit_solution_target(RuntimeOrigin::signed(1), H256::zero(), work),
+            Error::<Test>::PalletPaused
+        );
+        // Pausing again is still possible.
+        assert_ok!(Ctf::pause_pallet(RuntimeOrigin::root()));
+
+        assert_ok!(Ctf::unpause_pallet(RuntimeOrigin::root()));
+        assert_ok!(Ctf::enter_lottery(RuntimeOrigin::signed(1), work));
+    });
+}
+
+#[test]
+fn configured_randomness_selects_the_winner() {
+    new_test_ext().execute_with(|| {
+        fill_lottery(1, 20);
+        let entrants: Vec<u64> = LotteryEntries::<Test>::iter_keys().collect();
+
+        // The first four bytes, read big-endian, pick the winner's index.
+        let mut seed = [0u8; 32];
+        seed[..4].copy_from_slice(&45u32.to_be_bytes());
+        RandomnessOverride::set(Some(H256(seed)));
+        next_block();
+        RandomnessOverride::set(None);
+
+        assert_eq!(Ctf::recent_winners()[0].0, entrants[45 % 20]);
+    });
+}
+
+#[test]
+fn merge_scores_moves_points_and_disables_source() {
+    new_test_ext().execute_with(|| {
+        Score::<Test>::insert(1, enabled(300));
+        Score::<Test>::insert(2, enabled(50));
+        Leaderboard::<Test>::put(BoundedVec::truncate_from(vec![(1, 300), (2, 50)]));
+
+        assert_ok!(Ctf::merge_scores(RuntimeOrigin::root(), 1, 2));
+
+        assert_eq!(Score::<Test>::get(1), ScoreState::Disabled);
+        assert_eq!(Score::<Test>::get(2), enabled(350));
+        assert_eq!(Ctf::leaderboard(), vec![(2, 350)]);
+        System::assert_last_event(
+            Event::ScoresMerged {
+                from: 1,
+                into: 2,
+                moved: 300,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn merge_scores_rejects_disabled_target() {
+    new_test_ext().execute_with(|| {
+        Score::<Test>::insert(1, enabled(300));
+        Score::<Test>::insert(2, ScoreState::Disabled);
+
+        assert_noop!(
+            Ctf::merge_scores(RuntimeOrigin::root(), 1, 2),
+            Error::<Test>::ScoreDisabled
+        );
+        assert_noop!(Ctf::merge_scores(RuntimeOrigin::signed(1), 1, 3), BadOrigin);
+    });
+}
+
+#[test]
+fn block_author_receives_bonus_unless_withdrawn() {
+    use pallet_authorship::EventHandler;
+
+    new_test_ext().execute_with(|| {
+        Ctf::note_author(1);
+        assert_eq!(Score::<Test>::get(1), enabled(10));
+        assert_eq!(Ctf::leaderboard(), vec![(1, 10)]);
+        System::assert_last_event(
+            Event::AuthorBonusAwarded {
+                author: 1,
+                points: 10,
+            }
+            .into(),
+        );
+
+        Score::<Test>::insert(2, ScoreState::Disabled);
+        Ctf::note_author(2);
+        assert_eq!(Score::<Test>::get(2), ScoreState::Disabled);
+    });
+}
+
+#[test]
+fn lottery_below_minimum_entrants_is_not_drawn() {
+    new_test_ext().execute_with(|| {
+        LotteryThreshold::set(1);
+        MinLotteryEntrants::set(3);
+        fill_lottery(1, 1);
+
+        next_block();
+
+        System::assert_last_event(
+            Event::LotteryNoWinner {
+                reason: LotteryNoWinnerReason::TooFewEntrants,
+            }
+            .into(),
+        );
+        assert!(Ctf::recent_winners().is_empty());
+        assert_eq!(LotteryEntryCount::<Test>::get(), 1);
+        assert!(LotteryEntries::<Test>::contains_key(1));
+
+        // Once enough players joined the draw goes ahead.
+        fill_lottery(2, 2);
+        next_block();
+        assert_eq!(Ctf::recent_winners().len(), 1);
+        assert_eq!(LotteryEntryCount::<Test>::get(), 0);
+    });
+}
+
+#[test]
+fn submission_quota_resets_after_the_period() {
+    new_test_ext().execute_with(|| {
+        SubmissionQuota::set(2);
+        let submit = || {
+            let work = mine(1, 1);
+            Ctf::submit_solution(RuntimeOrigin::signed(1), 1, work)
+        };
+
+        assert_ok!(submit());
+        System::inc_account_nonce(1);
+        assert_ok!(submit());
+        System::inc_account_nonce(1);
+        assert_noop!(submit(), Error::<Test>::QuotaExceeded);
+
+        // The first solution at block 1 opened the window, which lasts ten blocks.
+        System::set_block_number(10);
+        assert_noop!(submit(), Error::<Test>::QuotaExceeded);
+        System::set_block_number(11);
+        assert_ok!(submit());
+        assert_eq!(points(1), 3);
+    });
+}
+
+#[test]
+fn leaderboard_insert_keeps_descending_order() {
+    let mut board = BoundedLeaderboard::<u64, ConstU32<3>>::default();
+    assert!(sorted_insert_or_update(&mut board, 1, 10));
+    assert!(sorted_insert_or_update(&mut board, 2, 30));
+    assert!(sorted_insert_or_update(&mut board, 3, 20));
+    assert_eq!(board.to_vec(), vec![(2, 30), (3, 20), (1, 10)]);
+
+    // A full board only admits scores above its lowest entry, evicting it.
+    assert!(!sorted_insert_or_update(&mut board, 4, 10));
+    assert!(sorted_insert_or_update(&mut board, 4, 25));
+    assert_eq!(board.to_vec(), vec![(2, 30), (4, 25), (3, 20)]);
+}
+
+#[test]
+fn leaderboard_update_moves_existing_entry() {
+    let mut board = BoundedLeaderboard::<u64, ConstU32<3>>::default();
+    for (who, score) in [(1, 30), (2, 20), (3, 10)] {
+        sorted_insert_or_update(&mut board, who, score);
+    }
+
+    assert!(sorted_insert_or_update(&mut board, 3, 40));
+    assert_eq!(board.to_vec(), vec![(3, 40), (1, 30), (2, 20)]);
+
+    // Ties rank the earlier holder of the score first.
+    assert!(sorted_insert_or_update(&mut board, 3, 20));
+    assert_eq!(board.to_vec(), vec![(1, 30), (2, 20), (3, 20)]);
+}
+
+#[test]
+fn leaderboard_remove_keeps_the_rest_in_order() {
+    let mut board = BoundedLeaderboard::<u64, ConstU32<3>>::default();
+    for (who, score) in [(1, 30), (2, 20), (3, 10)] {
+        sorted_insert_or_update(&mut board, who, score);
+    }
+
+    assert!(remove_entry(&mut board, &2));
+    assert!(!remove_entry(&mut board, &4));
+    assert_eq!(board.to_vec(), vec![(1, 30), (3, 10)]);
+}
+
+#[test]
+fn accepted_solutions_update_the_leaderboard() {
+    new_test_ext().execute_with(|| {
+        for (who, difficulty) in [(1, 3), (2, 5), (3, 4)] {
+            let work = mine(who, difficulty);
+            assert_ok!(Ctf::submit_solution(
+                RuntimeOrigin::signed(who),
+                difficulty,
+                work
+            ));
+        }
+
+        assert_eq!(Ctf::leaderboard(), vec![(2, 16), (3, 8), (1, 4)]);
+    });
+}
+
+#[test]
+fn player_count_tracks_distinct_accounts() {
+    new_test_ext().execute_with(|| {
+        for who in [1, 2, 1, 3] {
+            let work = mine(who, 1);
+            assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(who), 1, work));
+            System::inc_account_nonce(who);
+        }
+        assert_eq!(Ctf::player_count(), 3);
+
+        // Withdrawn players keep their entry and stay counted.
+        assert_ok!(Ctf::withdraw(RuntimeOrigin::signed(2)));
+        assert_eq!(Ctf::player_count(), 3);
+    });
+}
+
+#[test]
+fn ensure_enabled_returns_points_or_rejects_disabled() {
+    new_test_ext().execute_with(|| {
+        assert!(matches!(
+            Ctf::ensure_enabled(&1),
+            Ok(PlayerStats { points: 0, .. })
+        ));
+        Score::<Test>::insert(1, enabled(42));
+        assert!(matches!(
+            Ctf::ensure_enabled(&1),
+            Ok(PlayerStats { points: 42, .. })
+        ));
+
+        Score::<Test>::insert(1, ScoreState::Disabled);
+        assert!(matches!(
+            Ctf::ensure_enabled(&1),
+            Err(Error::<Test>::ScoreDisabled)
+        ));
+    });
+}
+
+#[test]
+fn target_submission_matches_integer_difficulty() {
+    new_test_ext().execute_with(|| {
+        // The target of difficulty 6 is 2^250.
+        let mut target = [0u8; 32];
+        target[0] = 1 << 2;
+        let target = H256(target);
+
+        let work = mine(1, 6);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 6, work));
+        let work = mine(2, 6);
+        assert_ok!(Ctf::submit_solution_target(
+            RuntimeOrigin::signed(2),
+            target,
+            work
+        ));
+
+        assert_eq!(Score::<Test>::get(1), Score::<Test>::get(2));
+        System::assert_last_event(
+            Event::SolutionAccepted {
+                who: 2,
+                difficulty: 6,
+                new_score: 32,
+                tier: Some(b"Bronze".to_vec()),
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn target_between_difficulties_scores_the_easier_one() {
+    new_test_ext().execute_with(|| {
+        // Just above the target of difficulty 6, so only as hard as difficulty 5.
+        let mut target = [0u8; 32];
+        target[0] = 1 << 2;
+        target[31] = 1;
+
+        let work = mine_below(1, 5, U256::from_big_endian(&target));
+        assert_ok!(Ctf::submit_solution_target(
+            RuntimeOrigin::signed(1),
+            H256(target),
+            work
+        ));
+        assert_eq!(points(1), 16);
+    });
+}
+
+#[test]
+fn target_easier_than_min_difficulty_is_rejected() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Ctf::submit_solution_target(
+                RuntimeOrigin::signed(1),
+                H256::repeat_byte(0xff),
+                H256::zero()
+            ),
+            Error::<Test>::InvalidDifficulty
+        );
+        assert_noop!(
+            Ctf::submit_solution_target(RuntimeOrigin::signed(1), H256::zero(), H256::zero()),
+            Error::<Test>::InvalidDifficulty
+        );
+    });
+}
+
+#[test]
+fn rejected_solutions_are_logged() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Ctf::submit_solution(
+            RuntimeOrigin::signed(1),
+            30,
+            H256::zero()
+        ));
+
+        assert_eq!(Ctf::verification_log(), vec![(1, 30, false, 1)]);
+        assert_eq!(points(1), 0);
+        System::assert_last_event(
+            Event::SolutionRejected {
+                who: 1,
+                difficulty: 30,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn accepted_solutions_are_logged() {
+    new_test_ext().execute_with(|| {
+        let work = mine(1, 2);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 2, work));
+        System::set_block_number(2);
+        System::inc_account_nonce(1);
+        let work = mine(1, 3);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 3, work));
+
+        assert_eq!(
+            Ctf::verification_log(),
+            vec![(1, 2, true, 1), (1, 3, true, 2)]
+        );
+    });
+}
+
+#[test]
+fn verification_log_keeps_latest_thousand() {
+    new_test_ext().execute_with(|| {
+        let full: Vec<_> = (0..1000u64).map(|i| (i, 1, true, 1)).collect();
+        crate::VerificationLog::<Test>::put(BoundedVec::truncate_from(full));
+
+        let work = mine(1000, 2);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1000), 2, work));
+
+        let log = Ctf::verification_log();
+        assert_eq!(log.len(), 1000);
+        assert_eq!(log[0].0, 1);
+        assert_eq!(log[999], (1000, 2, true, 1));
+    });
+}
+
+#[test]
+fn withdrawals_add_up_to_total_withdrawn() {
+    new_test_ext().execute_with(|| {
+        Score::<Test>::insert(1, enabled(100));
+        Score::<Test>::insert(2, enabled(250));
+        Score::<Test>::insert(3, enabled(0));
+
+        assert_ok!(Ctf::withdraw(RuntimeOrigin::signed(1)));
+        assert_ok!(Ctf::withdraw(RuntimeOrigin::signed(2)));
+        assert_eq!(Ctf::total_withdrawn(), 350);
+
+        // Withdrawing nothing adds nothing.
+        assert_ok!(Ctf::withdraw(RuntimeOrigin::signed(3)));
+        assert_eq!(Ctf::total_withdrawn(), 350);
+    });
+}
+
+#[test]
+fn submissions_build_up_reputation() {
+    new_test_ext().execute_with(|| {
+        for difficulty in [4, 6] {
+            let work = mine(1, difficulty);
+            assert_ok!(Ctf::submit_solution(
+                RuntimeOrigin::signed(1),
+                difficulty,
+                work
+            ));
+            System::inc_account_nonce(1);
+        }
+
+        let ScoreState::Enabled(stats) = Score::<Test>::get(1) else {
+            panic!("account 1 is enabled");
+        };
+        assert_eq!(
+            stats,
+            PlayerStats {
+                points: 8 + 32,
+                submissions: 2,
+                avg_difficulty_times_100: 500,
+            }
+        );
+        // 40 points at an average difficulty of 5, relative to 20.
+        assert_eq!(stats.reputation_score(), 10);
+        assert_eq!(Ctf::reputation_score(&1), 10);
+    });
+}
+
+#[test]
+fn transfer_points_moves_points_between_players() {
+    new_test_ext().execute_with(|| {
+        Score::<Test>::insert(1, enabled(100));
+        Leaderboard::<Test>::put(BoundedVec::truncate_from(vec![(1, 100)]));
+
+        assert_ok!(Ctf::transfer_points(RuntimeOrigin::signed(1), 2, 40));
+
+        assert_eq!(points(1), 60);
+        assert_eq!(points(2), 40);
+        assert_eq!(Ctf::leaderboard(), vec![(1, 60), (2, 40)]);
+        assert_noop!(
+            Ctf::transfer_points(RuntimeOrigin::signed(1), 2, 61),
+            Error::<Test>::InsufficientScore
+        );
+    });
+}
+
+#[test]
+fn transfer_to_disabled_is_rejected_in_reject_mode() {
+    new_test_ext().execute_with(|| {
+        Score::<Test>::insert(1, enabled(100));
+        Score::<Test>::insert(2, ScoreState::Disabled);
+
+        assert_noop!(
+            Ctf::transfer_points(RuntimeOrigin::signed(1), 2, 40),
+            Error::<Test>::ScoreDisabled
+        );
+    });
+}
+
+#[test]
+fn transfer_to_disabled_is_burned_in_burn_mode() {
+    new_test_ext().execute_with(|| {
+        TransferToDisabled::set(DisabledRecipientPolicy::Burn);
+        Score::<Test>::insert(1, enabled(100));
+        Score::<Test>::insert(2, ScoreState::Disabled);
+
+        assert_ok!(Ctf::transfer_points(RuntimeOrigin::signed(1), 2, 40));
+        assert_ok!(Ctf::transfer_points(RuntimeOrigin::signed(1), 2, 15));
+
+        assert_eq!(points(1), 45);
+        assert_eq!(Score::<Test>::get(2), ScoreState::Disabled);
+        assert_eq!(Ctf::total_burned(), 55);
+        System::assert_last_event(Event::PointsBurned { amount: 15 }.into());
+    });
+}
+
+#[test]
+fn stale_randomness_prevents_the_draw() {
+    new_test_ext_with_randomness(H256::repeat_byte(7)).execute_with(|| {
+        MaxRandomnessAge::set(5);
+
+        // The randomness source gets stuck at block 2.
+        RandomnessKnownSince::set(Some(2));
+        fill_lottery(1, 20);
+        System::set_block_number(7);
+        next_block();
+
+        System::assert_last_event(
+            Event::LotteryNoWinner {
+                reason: LotteryNoWinnerReason::StaleRandomness,
+            }
+            .into(),
+        );
+        assert!(Ctf::recent_winners().is_empty());
+        assert_eq!(LotteryEntryCount::<Test>::get(), 20);
+    });
+}
+
+#[test]
+fn unseeded_randomness_prevents_the_draw() {
+    new_test_ext().execute_with(|| {
+        MaxRandomnessAge::set(5);
+        fill_lottery(1, 20);
+
+        next_block();
+
+        System::assert_last_event(
+            Event::LotteryNoWinner {
+                reason: LotteryNoWinnerReason::StaleRandomness,
+            }
+            .into(),
+        );
+        assert_eq!(LotteryEntryCount::<Test>::get(), 20);
+    });
+}
+
+#[test]
+fn self_chained_randomness_reports_its_last_rotation() {
+    new_test_ext_with_randomness(H256::repeat_byte(7)).execute_with(|| {
+        use frame::traits::Randomness;
+        type Chained = crate::SelfChainedRandomness<Test>;
+        assert_eq!(Chained::random(b""), (H256::repeat_byte(7), 0));
+
+        next_block();
+
+        let block = System::block_number();
+        assert_eq!(RandomnessUpdatedAt::<Test>::get(), Some(block));
+        assert_eq!(
+            Chained::random(b""),
+            (LotteryRandomness::<Test>::get().unwrap(), block)
+        );
+    });
+}
+
+#[test]
+fn fresh_randomness_allows_the_draw() {
+    new_test_ext_with_randomness(H256::repeat_byte(7)).execute_with(|| {
+        MaxRandomnessAge::set(5);
+        RandomnessKnownSince::set(Some(3));
+        fill_lottery(1, 20);
+        System::set_block_number(7);
+
+        next_block();
+
+        assert_eq!(Ctf::recent_winners().len(), 1);
+    });
+}
+
+#[test]
+fn unsigned_lottery_entry_is_validated_and_applied() {
+    use sp_runtime::{
+        traits::ValidateUnsigned,
+        transaction_validity::{InvalidTransaction, TransactionSource},
+    };
+
+    new_test_ext().execute_with(|| {
+        let work = mine(1, 4);
+        let call = crate::Call::enter_lottery_unsigned { who: 1, work };
+        let validity = Ctf::validate_unsigned(TransactionSource::External, &call).unwrap();
+        assert_eq!(
+            validity.provides,
+            vec![("CtfLottery", (1u64, b"lottery", 0u32).encode()).encode()]
+        );
+
+        let bad = crate::Call::enter_lottery_unsigned {
+            who: 2,
+            work: H256::repeat_byte(0xff),
+        };
+        assert_eq!(
+            Ctf::validate_unsigned(TransactionSource::External, &bad),
+            Err(InvalidTransaction::BadProof.into())
+        );
+
+        assert_ok!(Ctf::enter_lottery_unsigned(RuntimeOrigin::none(), 1, work));
+        assert!(LotteryEntries::<Test>::contains_key(1));
+        assert_eq!(LotteryNonce::<Test>::get(1), 1);
+    });
+}
+
+#[test]
+fn unsigned_lottery_proof_cannot_be_replayed() {
+    use sp_runtime::{
+        traits::ValidateUnsigned,
+        transaction_validity::{InvalidTransaction, TransactionSource},
+    };
+
+    new_test_ext().execute_with(|| {
+        let work = mine(1, 4);
+        assert_ok!(Ctf::enter_lottery_unsigned(RuntimeOrigin::none(), 1, work));
+        assert_ok!(Ctf::leave_lottery(RuntimeOrigin::signed(1)));
+
+        // The account nonce has not moved, but the proof was spent on the first entry
+        assert_eq!(System::account_nonce(1), 0);
+        let call = crate::Call::enter_lottery_unsigned { who: 1, work };
+        assert_eq!(
+            Ctf::validate_unsigned(TransactionSource::External, &call),
+            Err(InvalidTransaction::BadProof.into())
+        );
+        assert_noop!(
+            Ctf::enter_lottery_unsigned(RuntimeOrigin::none(), 1, work),
+            Error::<Test>::BadProof
+        );
+
+        // A proof for the next lottery nonce enters again
+        let work = Ctf::mine(&1, LotteryNonce::<Test>::get(1), 4);
+        assert_ok!(Ctf::enter_lottery_unsigned(RuntimeOrigin::none(), 1, work));
+    });
+}
+
+#[test]
+fn unsigned_lottery_entries_that_would_fail_are_not_pooled() {
+    use sp_runtime::{
+        traits::ValidateUnsigned,
+        transaction_validity::{InvalidTransaction, TransactionSource},
+    };
+
+    new_test_ext().execute_with(|| {
+        let work = mine(1, 4);
+        let call = crate::Call::enter_lottery_unsigned { who: 1, work };
+        let validate = || Ctf::validate_unsigned(TransactionSource::External, &call);
+        let rejected = Err(InvalidTransaction::Call.into());
+
+        crate::PalletPaused::<Test>::put(true);
+        assert_eq!(validate(), rejected);
+        crate::PalletPaused::<Test>::kill();
+
+        crate::GameOver::<Test>::put(true);
+        assert_eq!(validate(), rejected);
+        crate::GameOver::<Test>::kill();
+
+        MinScoreForLottery::set(10);
+        assert_eq!(validate(), rejected);
+        MinScoreForLottery::set(0);
+
+        Score::<Test>::insert(1, ScoreState::Disabled);
+        assert_eq!(validate(), rejected);
+        Score::<Test>::insert(1, enabled(0));
+
+        assert!(validate().is_ok());
+    });
+}
+
+#[test]
+fn unsigned_lottery_entry_takes_no_cost_or_stake() {
+    new_test_ext().execute_with(|| {
+        LotteryEntryCost::set(5);
+        LotteryStake::set(100);
+        Score::<Test>::insert(1, enabled(20));
+        let reserved = Balances::reserved_balance(1);
+
+        let work = mine(1, 4);
+        assert_ok!(Ctf::enter_lottery_unsigned(RuntimeOrigin::none(), 1, work));
+
+        assert_eq!(points(1), 20);
+        assert_eq!(LotteryBonusPool::<Test>::get(), 0);
+        assert_eq!(Balances::reserved_balance(1), reserved);
+        assert_eq!(LotteryEntries::<Test>::get(1), Some(0));
+    });
+}
+
+#[test]
+fn unsigned_lottery_entry_can_be_disabled() {
+    new_test_ext().execute_with(|| {
+        AllowUnsignedLottery::set(false);
+        let work = mine(1, 4);
+        assert_noop!(
+            Ctf::enter_lottery_unsigned(RuntimeOrigin::none(), 1, work),
+            Error::<Test>::UnsignedLotteryDisabled
+        );
+        assert_noop!(
+            Ctf::enter_lottery_unsigned(RuntimeOrigin::signed(1), 1, work),
+            BadOrigin
+        );
+    });
+}
+
+#[test]
+fn difficulties_map_to_the_highest_reached_tier() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(Ctf::tier_for(3), None);
+        assert_eq!(Ctf::tier_for(4), Some(b"Bronze".to_vec()));
+        assert_eq!(Ctf::tier_for(11), Some(b"Silver".to_vec()));
+        assert_eq!(Ctf::tier_for(64), Some(b"Gold".to_vec()));
+
+        let work = mine(1, 8);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 8, work));
+        System::assert_last_event(
+            Event::SolutionAccepted {
+                who: 1,
+                difficulty: 8,
+                new_score: 128,
+                tier: Some(b"Silver".to_vec()),
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn safe_mode_accepts_only_minimum_difficulty_for_one_point() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Ctf::set_safe_mode(RuntimeOrigin::signed(1), true),
+            BadOrigin
+        );
+        assert_ok!(Ctf::set_safe_mode(RuntimeOrigin::root(), true));
+        System::assert_last_event(Event::SafeModeChanged { on: true }.into());
+
+        let work = mine(1, 6);
+        assert_noop!(
+            Ctf::submit_solution(RuntimeOrigin::signed(1), 6, work),
+            Error::<Test>::InvalidDifficulty
+        );
+
+        let work = mine(1, 1);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 1, work));
+        assert_eq!(points(1), 1);
+    });
+}
+
+#[test]
+fn escrowed_points_are_credited_after_the_escrow_period() {
+    new_test_ext().execute_with(|| {
+        EscrowPeriod::set(2);
+
+        let work = mine(1, 3);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 3, work));
+        assert_eq!(points(1), 0);
+        assert_eq!(EscrowedScore::<Test>::get(1).into_inner(), vec![(4, 3)]);
+        assert_eq!(EscrowReleases::<Test>::get(3).into_inner(), vec![1]);
+
+        // Blocks without releases only read the empty index
+        next_block();
+        assert_eq!(points(1), 0);
+        assert_eq!(
+            Ctf::on_initialize(2),
+            <Test as frame_system::Config>::DbWeight::get().reads_writes(1, 1)
+        );
+
+        next_block();
+        assert_eq!(points(1), 4);
+        assert!(!EscrowedScore::<Test>::contains_key(1));
+        assert!(!EscrowReleases::<Test>::contains_key(3));
+        assert_eq!(Ctf::leaderboard(), vec![(1, 4)]);
+        System::assert_last_event(Event::EscrowReleased { who: 1, points: 4 }.into());
+    });
+}
+
+#[test]
+fn escrowed_points_of_withdrawn_accounts_are_forfeited() {
+    new_test_ext().execute_with(|| {
+        EscrowPeriod::set(1);
+
+        let work = mine(1, 2);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 2, work));
+        assert_ok!(Ctf::withdraw(RuntimeOrigin::signed(1)));
+
+        next_block();
+        assert_eq!(Score::<Test>::get(1), ScoreState::Disabled);
+        assert!(!EscrowedScore::<Test>::contains_key(1));
+    });
+}
+
+#[test]
+fn submission_filter_can_veto_solutions() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Ctf::submit_solution(RuntimeOrigin::signed(1), 51, H256::zero()),
+            Error::<Test>::SubmissionVetoed
+        );
+
+        let work = mine(1, 2);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 2, work));
+        assert_eq!(points(1), 2);
+    });
+}
+
+#[test]
+fn awards_are_rounded_down_to_the_points_granularity() {
+    new_test_ext().execute_with(|| {
+        PointsGranularity::set(100);
+
+        // A raw award of 256 rounds down to 200
+        let work = mine(1, 9);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 9, work));
+        assert_eq!(points(1), 200);
+
+        // Awards below the granularity still earn one multiple
+        let work = mine(2, 3);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(2), 3, work));
+        assert_eq!(points(2), 100);
+    });
+}
+
+#[test]
+fn solutions_mint_the_balance_reward_per_point() {
+    new_test_ext().execute_with(|| {
+        // No reward is minted by default
+        let work = mine(1, 3);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 3, work));
+        assert_eq!(Balances::free_balance(1), 0);
+
+        RewardPerPoint::set(3);
+        let work = mine(2, 9);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(2), 9, work));
+        assert_eq!(points(2), 256);
+        assert_eq!(Balances::free_balance(2), 768);
+        assert_eq!(Balances::total_issuance(), 768);
+    });
+}
+
+#[test]
+fn rewards_below_the_existential_deposit_are_reported() {
+    new_test_ext().execute_with(|| {
+        RewardPerPoint::set(3);
+        ExistentialDeposit::set(10);
+
+        // 2 points earn 6, too little to create the account
+        let work = mine(1, 2);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 2, work));
+        assert_eq!(points(1), 2);
+        assert_eq!(Balances::free_balance(1), 0);
+        assert_eq!(Balances::total_issuance(), 0);
+        System::assert_has_event(Event::RewardBelowExistentialDeposit { who: 1, reward: 6 }.into());
+
+        // The same reward is minted into an account that already exists
+        assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), 1, 10));
+        System::inc_account_nonce(1);
+        let work = mine(1, 2);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 2, work));
+        assert_eq!(Balances::free_balance(1), 16);
+    });
+}
+
+#[test]
+fn escrowed_points_mint_their_reward_on_release() {
+    new_test_ext().execute_with(|| {
+        RewardPerPoint::set(3);
+        EscrowPeriod::set(1);
+
+        let work = mine(1, 3);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 3, work));
+        let work = mine(2, 3);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(2), 3, work));
+        assert_eq!(Balances::total_issuance(), 0);
+
+        // Forfeited points earn nothing
+        assert_ok!(Ctf::withdraw(RuntimeOrigin::signed(2)));
+
+        next_block();
+        assert_eq!(points(1), 4);
+        assert_eq!(Balances::free_balance(1), 12);
+        assert_eq!(Balances::free_balance(2), 0);
+        assert_eq!(Balances::total_issuance(), 12);
+        assert_eq!(Ctf::total_burned(), 4);
+        System::assert_has_event(Event::PointsBurned { amount: 4 }.into());
+    });
+}
+
+#[test]
+fn try_state_catches_corrupted_storage() {
+    new_test_ext().execute_with(|| {
+        let work = mine(1, 4);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 4, work));
+        fill_lottery(2, 2);
+        assert_ok!(Ctf::do_try_state());
+
+        LotteryEntryCount::<Test>::put(3);
+        assert!(Ctf::do_try_state().is_err());
+        LotteryEntryCount::<Test>::put(2);
+
+        // Raw bytes that are not a `ScoreState`
+        let key = Score::<Test>::hashed_key_for(5);
+        frame::deps::sp_io::storage::set(&key, &[9]);
+        assert!(Ctf::do_try_state().is_err());
+        frame::deps::sp_io::storage::clear(&key);
+
+        Leaderboard::<Test>::put(BoundedVec::truncate_from(vec![(6, 10)]));
+        assert!(Ctf::do_try_state().is_err());
+    });
+}
+
+#[test]
+fn try_state_holds_after_a_completed_draw() {
+    new_test_ext().execute_with(|| {
+        let work = mine(1, 4);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 4, work));
+        fill_lottery(2, 20);
+        next_block();
+
+        assert_eq!(Ctf::recent_winners().len(), 1);
+        assert_ok!(Ctf::do_try_state());
+    });
+}
+
+#[test]
+fn paging_through_exported_scores_reconstructs_the_map() {
+    new_test_ext().execute_with(|| {
+        for who in 1..=7 {
+            Score::<Test>::insert(who, enabled(who * 10));
+        }
+        Score::<Test>::insert(8, ScoreState::Disabled);
+
+        let mut exported = Vec::new();
+        let mut start = None;
+        loop {
+            let (page, cursor) = Ctf::export_scores(start, 3);
+            assert!(page.len() <= 3);
+            exported.extend(page);
+            match cursor {
+                Some(_) => start = cursor,
+                None => break,
+            }
+        }
+
+        exported.sort_by_key(|(who, _)| *who);
+        let mut stored: Vec<_> = Score::<Test>::iter().collect();
+        stored.sort_by_key(|(who, _)| *who);
+        assert_eq!(exported, stored);
+        assert_eq!(exported.len(), 8);
+
+        // An exact final page reports no cursor
+        assert_eq!(Ctf::export_scores(None, 8).1, None);
+    });
+}
+
+#[test]
+fn partial_withdrawals_start_a_withdrawal_cooldown() {
+    new_test_ext().execute_with(|| {
+        WithdrawCooldown::set(10);
+        Score::<Test>::insert(1, enabled(100));
+
+        assert_noop!(
+            Ctf::withdraw_partial(RuntimeOrigin::signed(1), 101),
+            Error::<Test>::InsufficientScore
+        );
+        assert_ok!(Ctf::withdraw_partial(RuntimeOrigin::signed(1), 40));
+        assert_eq!(points(1), 60);
+        assert_eq!(Ctf::total_withdrawn(), 40);
+        System::assert_last_event(
+            Event::PartiallyWithdrawn {
+                who: 1,
+                points: 40,
+                remaining: 60,
+            }
+            .into(),
+        );
+
+        // Neither kind of withdrawal is allowed until the cooldown is over
+        assert_noop!(
+            Ctf::withdraw_partial(RuntimeOrigin::signed(1), 10),
+            Error::<Test>::WithdrawCooldownActive
+        );
+        assert_noop!(
+            Ctf::withdraw(RuntimeOrigin::signed(1)),
+            Error::<Test>::WithdrawCooldownActive
+        );
+
+        System::set_block_number(11);
+        assert_ok!(Ctf::withdraw(RuntimeOrigin::signed(1)));
+        assert_eq!(Ctf::total_withdrawn(), 100);
+        assert_eq!(WithdrawCooldownUntil::<Test>::get(1), None);
+    });
+}
+
+#[test]
+fn importing_scores_twice_yields_the_same_state() {
+    new_test_ext().execute_with(|| {
+        Score::<Test>::insert(1, enabled(5));
+        let entries = BoundedVec::truncate_from(vec![
+            (1, enabled(300)),
+            (2, enabled(200)),
+            (3, ScoreState::Disabled),
+        ]);
+
+        assert_noop!(
+            Ctf::import_scores(RuntimeOrigin::signed(1), entries.clone()),
+            BadOrigin
+        );
+        assert_ok!(Ctf::import_scores(RuntimeOrigin::root(), entries.clone()));
+        let imported: Vec<_> = Score::<Test>::iter().collect();
+        let leaderboard = Ctf::leaderboard();
+        let player_count = Ctf::player_count();
+        assert_eq!(points(1), 300);
+        assert_eq!(Score::<Test>::get(3), ScoreState::Disabled);
+        assert_eq!(leaderboard, vec![(1, 300), (2, 200)]);
+
+        assert_ok!(Ctf::import_scores(RuntimeOrigin::root(), entries.clone()));
+        assert_eq!(Score::<Test>::iter().collect::<Vec<_>>(), imported);
+        assert_eq!(Ctf::leaderboard(), leaderboard);
+        assert_eq!(Ctf::player_count(), player_count);
+
+        AllowImport::set(false);
+        assert_noop!(
+            Ctf::import_scores(RuntimeOrigin::root(), entries),
+            Error::<Test>::ImportDisabled
+        );
+    });
+}
+
+#[test]
+fn solutions_must_advance_past_the_last_accepted_nonce() {
+    new_test_ext().execute_with(|| {
+        System::inc_account_nonce(1);
+        let work = mine(1, 4);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 4, work));
+        assert_eq!(LastPowNonce::<Test>::get(1), Some(1));
+
+        // A reorg resets the account nonce, the old solution stays spent
+        frame::deps::frame_system::Account::<Test>::mutate(1, |account| account.nonce = 1);
+        assert_noop!(
+            Ctf::submit_solution(RuntimeOrigin::signed(1), 4, work),
+            Error::<Test>::NonceNotAdvanced
+        );
+
+        System::inc_account_nonce(1);
+        let work = mine(1, 4);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 4, work));
+        assert_eq!(LastPowNonce::<Test>::get(1), Some(2));
+    });
+}
+
+#[test]
+fn harder_claims_weigh_more() {
+    let weight = |difficulty| {
+        crate::Call::<Test>::submit_solution {
+            difficulty,
+            work: H256::zero(),
+        }
+        .get_dispatch_info()
+        .call_weight
+    };
+    assert!(weight(40).ref_time() > weight(10).ref_time());
+    assert_eq!(
+        weight(40).ref_time() - weight(10).ref_time(),
+        30 * crate::WEIGHT_PER_DIFFICULTY
+    );
+
+    // An explicit target weighs as the difficulty it is scored at
+    let target_weight = |difficulty: u32| {
+        let target = H256((U256::one() << (256 - difficulty)).to_big_endian());
+        crate::Call::<Test>::submit_solution_target {
+            target,
+            work: H256::zero(),
+        }
+        .get_dispatch_info()
+        .call_weight
+    };
+    assert_eq!(target_weight(10), weight(10));
+    assert_eq!(target_weight(40), weight(40));
+}
+
+#[test]
+fn hints_are_stored_with_accepted_solutions() {
+    new_test_ext().execute_with(|| {
+        let hint: BoundedVec<u8, ConstU32<512>> = BoundedVec::truncate_from(b"xor key".to_vec());
+
+        // Rejected proofs leave no hint behind
+        assert_ok!(Ctf::submit_solution_with_hint(
+            RuntimeOrigin::signed(1),
+            8,
+            H256::zero(),
+            hint.clone()
+        ));
+        System::assert_last_event(
+            Event::SolutionRejected {
+                who: 1,
+                difficulty: 8,
+            }
+            .into(),
+        );
+        assert_eq!(SubmissionHints::<Test>::get(1, 0), None);
+
+        let work = mine(1, 8);
+        assert_ok!(Ctf::submit_solution_with_hint(
+            RuntimeOrigin::signed(1),
+            8,
+            work,
+            hint.clone()
+        ));
+        assert_eq!(points(1), 128);
+        assert_eq!(SubmissionHints::<Test>::get(1, 0), Some(hint.clone()));
+        System::assert_last_event(
+            Event::SolutionWithHint {
+                who: 1,
+                difficulty: 8,
+                hint_hash: H256::from(blake2_256(&hint)),
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn hints_are_not_stored_for_solutions_skipped_by_the_cooldown() {
+    new_test_ext().execute_with(|| {
+        SubmissionCooldown::set(5);
+        let hint: BoundedVec<u8, ConstU32<512>> = BoundedVec::truncate_from(b"xor key".to_vec());
+
+        let work = mine(1, 8);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 8, work));
+
+        System::inc_account_nonce(1);
+        let work = mine(1, 8);
+        assert_ok!(Ctf::submit_solution_with_hint(
+            RuntimeOrigin::signed(1),
+            8,
+            work,
+            hint
+        ));
+        assert_eq!(points(1), 128);
+        assert_eq!(SubmissionHints::<Test>::get(1, 1), None);
+        System::assert_last_event(
+            Event::CooldownActive {
+                who: 1,
+                blocks_remaining: 5,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn game_ends_after_the_maximum_total_submissions() {
+    new_test_ext().execute_with(|| {
+        MaxTotalSubmissions::set(2);
+        for who in [1, 2] {
+            let work = mine(who, 4);
+            assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(who), 4, work));
+        }
+        System::assert_last_event(
+            Event::GameEnded {
+                total_submissions: 2,
+            }
+            .into(),
+        );
+
+        let work = mine(3, 4);
+        assert_noop!(
+            Ctf::submit_solution(RuntimeOrigin::signed(3), 4, work),
+            Error::<Test>::GameOver
+        );
+        assert_noop!(
+            Ctf::enter_lottery(RuntimeOrigin::signed(1), mine(1, 4)),
+            Error::<Test>::GameOver
+        );
+
+        // Players can still leave with their points
+        assert_ok!(Ctf::withdraw(RuntimeOrigin::signed(1)));
+        assert_eq!(Score::<Test>::get(1), ScoreState::Disabled);
+    });
+}
+
+#[test]
+fn tiers_are_upgraded_but_never_downgraded() {
+    new_test_ext().execute_with(|| {
+        // 64 points stay below Bronze
+        let work = mine(1, 7);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 7, work));
+        assert_eq!(Ctf::get_tier(&1), None);
+
+        System::inc_account_nonce(1);
+        let work = mine(1, 11);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 11, work));
+        assert_eq!(Ctf::get_tier(&1), Some(BadgeTier::Silver));
+        System::assert_has_event(
+            Event::TierUpgrade {
+                who: 1,
+                from: None,
+                to: BadgeTier::Silver,
+            }
+            .into(),
+        );
+
+        // Spending points keeps the tier
+        assert_ok!(Ctf::withdraw_partial(RuntimeOrigin::signed(1), 1000));
+        assert_eq!(Ctf::get_tier(&1), Some(BadgeTier::Silver));
+
+        assert_ok!(Ctf::airdrop_points(
+            RuntimeOrigin::root(),
+            BoundedVec::truncate_from(vec![(1, 10_000)])
+        ));
+        assert_eq!(Ctf::get_tier(&1), Some(BadgeTier::Gold));
+        System::assert_has_event(
+            Event::TierUpgrade {
+                who: 1,
+                from: Some(BadgeTier::Silver),
+                to: BadgeTier::Gold,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn pow_preimage_is_what_verification_hashes() {
+    new_test_ext().execute_with(|| {
+        let work = mine(1, 12);
+        let preimage = Ctf::pow_preimage(&1, 0, 12, &work);
+        assert_eq!(
+            preimage,
+            (PowVersion::<Test>::get(), 1u64, 0u32, 12u32, work).encode()
+        );
+
+        // The hash of the preimage is what the proof is judged by
+        let hash = U256::from_little_endian(&blake2_256(&preimage));
+        assert!(hash < Ctf::difficulty_target(12).unwrap());
+        assert_eq!(
+            Ctf::verify_batch_pow(&1, 0, 12, vec![work]).unwrap(),
+            vec![true]
+        );
+        let bad = Ctf::pow_preimage(&1, 0, 12, &H256::zero());
+        assert_eq!(
+            U256::from_little_endian(&blake2_256(&bad)) < Ctf::difficulty_target(12).unwrap(),
+            Ctf::verify_batch_pow(&1, 0, 12, vec![H256::zero()]).unwrap()[0]
+        );
+    });
+}
+
+#[test]
+fn iterated_hashing_changes_which_work_is_valid() {
+    new_test_ext().execute_with(|| {
+        let candidates: Vec<H256> = (0u64..100).map(H256::from_low_u64_be).collect();
+        let single = Ctf::verify_batch_pow(&1, 0, 4, candidates.clone()).unwrap();
+        PowRounds::set(3);
+        let triple = Ctf::verify_batch_pow(&1, 0, 4, candidates.clone()).unwrap();
+        assert_ne!(single, triple);
+
+        // The third round hashes the digest of the second
+        let preimage = Ctf::pow_preimage(&1, 0, 4, &candidates[0]);
+        let hash = blake2_256(&blake2_256(&blake2_256(&preimage)));
+        assert_eq!(
+            U256::from_little_endian(&hash) < Ctf::difficulty_target(4).unwrap(),
+            triple[0]
+        );
+
+        let work = mine(1, 8);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 8, work));
+    });
+}
+
+#[test]
+fn scores_are_capped_across_submissions() {
+    new_test_ext().execute_with(|| {
+        MaxTotalScore::set(300);
+
+        let work = mine(1, 9);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 9, work));
+        assert_eq!(points(1), 256);
+
+        // Each submission stays under the cap, their sum does not
+        System::inc_account_nonce(1);
+        let work = mine(1, 9);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 9, work));
+        assert_eq!(points(1), 300);
+        System::assert_has_event(
+            Event::ScoreCapped {
+                who: 1,
+                attempted: 512,
+                capped: 300,
+            }
+            .into(),
+        );
+        assert_eq!(Ctf::total_burned(), 212);
+        assert_eq!(Ctf::leaderboard(), vec![(1, 300)]);
+
+        // Other credits respect the cap too
+        assert_ok!(Ctf::airdrop_points(
+            RuntimeOrigin::root(),
+            BoundedVec::truncate_from(vec![(1, 50)])
+        ));
+        assert_eq!(points(1), 300);
+    });
+}
+
+#[test]
+fn lifetime_cap_stops_scoring_once_reached() {
+    new_test_ext().execute_with(|| {
+        LifetimeScoreCap::set(320);
+
+        let work = mine(1, 9);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 9, work));
+        assert_eq!(LifetimeEarned::<Test>::get(1), 256);
+
+        // Spending points does not give back lifetime headroom
+        assert_ok!(Ctf::withdraw_partial(RuntimeOrigin::signed(1), 200));
+
+        // A solution that would go past the cap is rejected
+        System::inc_account_nonce(1);
+        let work = mine(1, 9);
+        assert_noop!(
+            Ctf::submit_solution(RuntimeOrigin::signed(1), 9, work),
+            Error::<Test>::LifetimeCapReached
+        );
+
+        // One that lands exactly on the cap is scored in full
+        let work = mine(1, 7);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 7, work));
+        assert_eq!(points(1), 120);
+        assert_eq!(LifetimeEarned::<Test>::get(1), 320);
+        System::assert_has_event(Event::LifetimeCapReached { who: 1 }.into());
+
+        System::inc_account_nonce(1);
+        let work = mine(1, 2);
+        assert_noop!(
+            Ctf::submit_solution(RuntimeOrigin::signed(1), 2, work),
+            Error::<Test>::LifetimeCapReached
+        );
+    });
+}
+
+#[test]
+fn withdraw_reports_the_leaderboard_rank() {
+    new_test_ext().execute_with(|| {
+        for (who, difficulty) in [(1, 2), (2, 4)] {
+            let work = mine(who, difficulty);
+            assert_ok!(Ctf::submit_solution(
+                RuntimeOrigin::signed(who),
+                difficulty,
+                work
+            ));
+        }
+
+        assert_ok!(Ctf::withdraw(RuntimeOrigin::signed(1)));
+        System::assert_last_event(
+            Event::Withdrawn {
+                who: 1,
+                points: 2,
+                rank: Some(2),
+            }
+            .into(),
+        );
+        assert_eq!(Ctf::leaderboard(), vec![(2, 8)]);
+    });
+}
+
+#[test]
+fn lottery_draw_countdown_counts_missing_entries() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(Ctf::lottery_draw_countdown(), 20);
+
+        fill_lottery(1, 18);
+        assert_eq!(Ctf::lottery_draw_countdown(), 2);
+
+        fill_lottery(19, 5);
+        assert_eq!(Ctf::lottery_draw_countdown(), 0);
+    });
+}
+
+#[test]
+fn same_block_solutions_decay_their_awards() {
+    new_test_ext().execute_with(|| {
+        BurstDecay::set(Percent::from_percent(50));
+
+        let mut awards = Vec::new();
+        for _ in 0..3 {
+            let before = points(1);
+            let work = mine(1, 5);
+            assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 5, work));
+            awards.push(points(1) - before);
+            System::inc_account_nonce(1);
+        }
+        assert_eq!(awards, vec![16, 8, 4]);
+
+        // The decay resets in the next block
+        next_block();
+        let work = mine(1, 5);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 5, work));
+        assert_eq!(points(1), 44);
+    });
+}
+
+#[test]
+fn verify_batch_pow_checks_every_candidate() {
+    new_test_ext().execute_with(|| {
+        let valid = mine(1, 8);
+        let invalid = (0u64..)
+            .map(H256::from_low_u64_be)
+            .find(|work| !Ctf::verify_batch_pow(&1, 0, 8, vec![*work]).unwrap()[0])
+            .unwrap();
+        assert_eq!(
+            Ctf::verify_batch_pow(&1, 0, 8, vec![valid, invalid, valid]).unwrap(),
+            vec![true, false, true]
+        );
+
+        let too_many = vec![valid; crate::MAX_BATCH_CANDIDATES + 1];
+        assert!(matches!(
+            Ctf::verify_batch_pow(&1, 0, 8, too_many),
+            Err(Error::<Test>::TooManyCandidates)
+        ));
+
+        // Difficulties past 256 bits are refused rather than shifted out of range
+        assert!(matches!(
+            Ctf::verify_batch_pow(&1, 0, 257, vec![valid]),
+            Err(Error::<Test>::InvalidDifficulty)
+        ));
+        // The bound lives in the target itself, so submissions are covered too
+        assert_eq!(Ctf::difficulty_target(256).unwrap(), U256::one());
+        assert!(matches!(
+            Ctf::difficulty_target(257),
+            Err(Error::<Test>::InvalidDifficulty)
+        ));
+    });
+}
+
+#[test]
+fn every_error_is_returned_by_the_failure_it_names() {
+    new_test_ext().execute_with(|| {
+        let signed = RuntimeOrigin::signed;
+
+        // Submissions
+        assert_noop!(
+            Ctf::submit_solution(signed(1), 0, H256::zero()),
+            Error::<Test>::InvalidDifficulty
+        );
+        assert_noop!(
+            Ctf::enter_lottery(signed(1), H256::repeat_byte(0xff)),
+            Error::<Test>::BadProof
+        );
+        assert_noop!(
+            Ctf::submit_solution(signed(1), 51, H256::zero()),
+            Error::<Test>::SubmissionVetoed
+        );
+
+        Score::<Test>::insert(2, ScoreState::Disabled);
+        let work = mine(2, 1);
+        assert_noop!(
+            Ctf::submit_solution(signed(2), 1, work),
+            Error::<Test>::ScoreDisabled
+        );
+
+        EscrowPeriod::set(5);
+        EscrowedScore::<Test>::insert(1, BoundedVec::truncate_from(vec![(1, 6); 64]));
+        let work = mine(1, 1);
+        assert_noop!(
+            Ctf::submit_solution(signed(1), 1, work),
+            Error::<Test>::EscrowFull
+        );
+        EscrowPeriod::set(0);
+
+        SubmissionQuota::set(1);
+        assert_ok!(Ctf::submit_solution(signed(1), 1, work));
+        System::inc_account_nonce(1);
+        let work = mine(1, 1);
+        assert_noop!(
+            Ctf::submit_solution(signed(1), 1, work),
+            Error::<Test>::QuotaExceeded
+        );
+        SubmissionQuota::set(0);
+
+        assert_ok!(Ctf::set_whitelist_mode(RuntimeOrigin::root(), true));
+        assert_noop!(
+            Ctf::submit_solution(signed(1), 1, work),
+            Error::<Test>::NotWhitelisted
+        );
+        assert_ok!(Ctf::set_whitelist_mode(RuntimeOrigin::root(), false));
+
+        assert_ok!(Ctf::pause_pallet(RuntimeOrigin::root()));
+        assert_noop!(
+            Ctf::submit_solution(signed(1), 1, work),
+            Error::<Test>::PalletPaused
+        );
+        assert_ok!(Ctf::unpause_pallet(RuntimeOrigin::root()));
+
+        assert_noop!(
+            Ctf::submit_solution_with_nonce(signed(1), 1, 10, work),
+            Error::<Test>::NonceOutOfWindow
+        );
+
+        LastPowNonce::<Test>::insert(1, 5);
+        assert_noop!(
+            Ctf::submit_solution(signed(1), 1, work),
+            Error::<Test>::NonceNotAdvanced
+        );
+        LastPowNonce::<Test>::insert(1, 0);
+
+        crate::GameOver::<Test>::put(true);
+        assert_noop!(
+            Ctf::submit_solution(signed(1), 1, work),
+            Error::<Test>::GameOver
+        );
+        crate::GameOver::<Test>::kill();
+
+        LifetimeScoreCap::set(10);
+        LifetimeEarned::<Test>::insert(1, 10);
+        assert_noop!(
+            Ctf::submit_solution(signed(1), 1, work),
+            Error::<Test>::LifetimeCapReached
+        );
+        LifetimeScoreCap::set(0);
+
+        // Withdrawals
+        assert_noop!(Ctf::withdraw(signed(9)), Error::<Test>::NeverPlayed);
+        assert_noop!(Ctf::withdraw(signed(2)), Error::<Test>::AlreadyWithdrawn);
+
+        WithdrawCooldown::set(5);
+        assert_ok!(Ctf::withdraw_partial(signed(1), 0));
+        assert_noop!(
+            Ctf::withdraw_partial(signed(1), 0),
+            Error::<Test>::WithdrawCooldownActive
+        );
+        WithdrawCooldownUntil::<Test>::remove(1);
+
+        // Lottery
+        let work = mine(3, 4);
+        LotteryEntryCount::<Test>::put(u32::MAX);
+        assert_noop!(
+            Ctf::enter_lottery(signed(3), work),
+            Error::<Test>::LotteryEntryFailed
+        );
+        LotteryEntryCount::<Test>::kill();
+
+        LotteryEntries::<Test>::insert(3, 0);
+        assert_noop!(
+            Ctf::enter_lottery(signed(3), work),
+            Error::<Test>::AlreadyInLottery
+        );
+
+        MinScoreForLottery::set(1);
+        let work = mine(4, 4);
+        assert_noop!(
+            Ctf::enter_lottery(signed(4), work),
+            Error::<Test>::InsufficientPointsForLottery
+        );
+        MinScoreForLottery::set(0);
+
+        LotteryEntryCost::set(1);
+        assert_noop!(
+            Ctf::enter_lottery(signed(4), work),
+            Error::<Test>::InsufficientScore
+        );
+        LotteryEntryCost::set(0);
+
+        AllowUnsignedLottery::set(false);
+        assert_noop!(
+            Ctf::enter_lottery_unsigned(RuntimeOrigin::none(), 4, work),
+            Error::<Test>::UnsignedLotteryDisabled
+        );
+
+        assert_noop!(Ctf::leave_lottery(signed(4)), Error::<Test>::NotInLottery);
+
+        // Points and codes
+        assert_noop!(
+            Ctf::merge_scores(RuntimeOrigin::root(), 1, 1),
+            Error::<Test>::MergeIntoSelf
+        );
+        assert_noop!(
+            Ctf::transfer_points(signed(1), 1, 1),
+            Error::<Test>::TransferToSelf
+        );
+        assert_noop!(
+            Ctf::redeem(signed(1), BoundedVec::truncate_from(b"unknown".to_vec())),
+            Error::<Test>::InvalidCode
+        );
+
+        // Administration
+        PowVersion::<Test>::put(u8::MAX);
+        assert_noop!(
+            Ctf::bump_pow_version(RuntimeOrigin::root(), 0),
+            Error::<Test>::PowVersionExhausted
+        );
+        PowVersion::<Test>::kill();
+
+        AllowImport::set(false);
+        assert_noop!(
+            Ctf::import_scores(RuntimeOrigin::root(), BoundedVec::new()),
+            Error::<Test>::ImportDisabled
+        );
+        AllowImport::set(true);
+
+        // Queries
+        assert!(matches!(
+            Ctf::verify_batch_pow(
+                &1,
+                0,
+                1,
+                vec![H256::zero(); crate::MAX_BATCH_CANDIDATES + 1]
+            ),
+            Err(Error::<Test>::TooManyCandidates)
+        ));
+    });
+}
+
+#[test]
+fn verification_does_not_depend_on_verbose_logging() {
+    new_test_ext().execute_with(|| {
+        let work = mine(1, 8);
+        for verbose in [false, true] {
+            VerboseVerification::set(verbose);
+            assert_ok!(Ctf::verify_pow_dry_run(RuntimeOrigin::signed(1), 8, work));
+            System::assert_last_event(
+                Event::ProofVerified {
+                    who: 1,
+                    difficulty: 8,
+                    valid: true,
+                }
+                .into(),
+            );
+        }
+    });
+}
+
+#[test]
+fn mined_work_is_accepted() {
+    new_test_ext().execute_with(|| {
+        System::inc_account_nonce(1);
+        let work = Ctf::mine(&1, 1, 6);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 6, work));
+        assert_eq!(points(1), 32);
+    });
+}
+
+#[test]
+fn possibly_replayed_nonces_need_a_harder_proof() {
+    new_test_ext().execute_with(|| {
+        let (byte, bit) = Ctf::nonce_bloom_position(&1, 0);
+        let mut filter = [0u8; 256];
+        filter[byte] = bit;
+        UsedNonces::<Test>::put(filter);
+
+        // A proof clearing the target of difficulty 4 but not of difficulty 5
+        let work = (0u64..)
+            .map(H256::from_low_u64_be)
+            .find(|work| {
+                let hash =
+                    U256::from_little_endian(&blake2_256(&(1u8, 1u64, 0u32, 4u32, work).encode()));
+                hash < U256::one() << 252 && hash >= U256::one() << 251
+            })
+            .unwrap();
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 4, work));
+        System::assert_last_event(
+            Event::SolutionRejected {
+                who: 1,
+                difficulty: 4,
+            }
+            .into(),
+        );
+        assert_eq!(points(1), 0);
+
+        // Accepted solutions set the bit of their pair
+        let work = mine(2, 4);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(2), 4, work));
+        let (byte, bit) = Ctf::nonce_bloom_position(&2, 0);
+        assert_ne!(UsedNonces::<Test>::get()[byte] & bit, 0);
+    });
+}
+
+#[test]
+fn draw_of_an_empty_pool_resets_the_count() {
+    new_test_ext().execute_with(|| {
+        MinLotteryEntrants::set(0);
+        LotteryEntryCount::<Test>::put(20);
+
+        next_block();
+        System::assert_last_event(
+            Event::LotteryNoWinner {
+                reason: LotteryNoWinnerReason::EmptyPool,
+            }
+            .into(),
+        );
+        assert_eq!(LotteryEntryCount::<Test>::get(), 0);
+        assert!(Ctf::recent_winners().is_empty());
+    });
+}
+
+#[test]
+fn prize_of_a_withdrawn_winner_is_dropped_without_rollover() {
+    new_test_ext().execute_with(|| {
+        LotteryThreshold::set(3);
+        fill_lottery(1, 3);
+        for who in 1..=3 {
+            Score::<Test>::insert(who, ScoreState::Disabled);
+        }
+
+        // The draw closes rather than failing and being retried every block
+        next_block();
+        System::assert_last_event(
+            Event::LotteryNoWinner {
+                reason: LotteryNoWinnerReason::WinnerWithdrawn,
+            }
+            .into(),
+        );
+        assert_eq!(LotteryEntries::<Test>::iter_keys().count(), 0);
+        assert_eq!(LotteryEntryCount::<Test>::get(), 0);
+        assert_eq!(LotteryBonusPool::<Test>::get(), 0);
+        assert_eq!(WinnerHistory::<Test>::get().len(), 0);
+    });
+}
+
+#[test]
+fn withdrawing_leaves_the_lottery() {
+    new_test_ext().execute_with(|| {
+        LotteryStake::set(10);
+        assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), 1, 100));
+        Score::<Test>::insert(1, enabled(10));
+        let work = mine(1, 4);
+        assert_ok!(Ctf::enter_lottery(RuntimeOrigin::signed(1), work));
+        assert_eq!(Balances::reserved_balance(1), 10);
+
+        assert_ok!(Ctf::withdraw(RuntimeOrigin::signed(1)));
+
+        assert!(!LotteryEntries::<Test>::contains_key(1));
+        assert_eq!(LotteryEntryCount::<Test>::get(), 0);
+        assert_eq!(Balances::reserved_balance(1), 0);
+    });
+}
+
+#[test]
+fn prize_of_a_withdrawn_winner_rolls_over_to_the_next_draw() {
+    new_test_ext().execute_with(|| {
+        RolloverPrize::set(true);
+        LotteryThreshold::set(1);
+
+        Score::<Test>::insert(1, ScoreState::Disabled);
+        fill_lottery(1, 1);
+        next_block();
+        System::assert_last_event(Event::PrizeRolledOver { amount: 800 }.into());
+        assert_eq!(LotteryBonusPool::<Test>::get(), 800);
+        assert_eq!(LotteryEntryCount::<Test>::get(), 0);
+        assert!(!LotteryEntries::<Test>::contains_key(1));
+
+        fill_lottery(2, 1);
+        next_block();
+        System::assert_last_event(
+            Event::LotteryWinnerSelected {
+                who: 2,
+                points_awarded: 1600,
+            }
+            .into(),
+        );
+        assert_eq!(points(2), 1600);
+    });
+}
+
+#[test]
+fn bumping_the_pow_version_invalidates_mined_work() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(PowVersion::<Test>::get(), 1);
+        let work = mine(1, 8);
+
+        assert_noop!(
+            Ctf::bump_pow_version(RuntimeOrigin::signed(1), 0),
+            BadOrigin
+        );
+        assert_ok!(Ctf::bump_pow_version(RuntimeOrigin::root(), 0));
+        System::assert_last_event(Event::PowVersionChanged { new_version: 2 }.into());
+
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 8, work));
+        assert_eq!(points(1), 0);
+        let work = mine(1, 8);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 8, work));
+
+        PowVersion::<Test>::put(u8::MAX);
+        assert_noop!(
+            Ctf::bump_pow_version(RuntimeOrigin::root(), 0),
+            Error::<Test>::PowVersionExhausted
+        );
+    });
+}
+
+#[test]
+fn raw_leaderboard_accounts_round_trip() {
+    new_test_ext().execute_with(|| {
+        for (who, difficulty) in [(1, 2), (2, 4), (3, 3)] {
+            let work = mine(who, difficulty);
+            assert_ok!(Ctf::submit_solution(
+                RuntimeOrigin::signed(who),
+                difficulty,
+                work
+            ));
+        }
+
+        let raw = Ctf::leaderboard_raw(2);
+        let decoded: Vec<(u64, u64)> = raw
+            .iter()
+            .map(|(bytes, score)| (u64::decode(&mut &bytes[..]).unwrap(), *score))
+            .collect();
+        assert_eq!(decoded, vec![(2, 8), (3, 4)]);
+    });
+}
+
+#[test]
+fn debug_print_score_leaves_the_state_untouched() {
+    new_test_ext().execute_with(|| {
+        Score::<Test>::insert(1, enabled(40));
+        Score::<Test>::insert(2, ScoreState::Disabled);
+
+        Ctf::debug_print_score(&1);
+        Ctf::debug_print_score(&2);
+        Ctf::debug_print_score(&3);
+        assert_eq!(Score::<Test>::get(1), enabled(40));
+        assert_eq!(Score::<Test>::get(2), ScoreState::Disabled);
+    });
+}
+
+#[test]
+fn leader_is_the_enabled_account_with_the_most_points() {
+    new_test_ext().execute_with(|| {
+        Score::<Test>::insert(1, enabled(40));
+        Score::<Test>::insert(2, enabled(90));
+        Score::<Test>::insert(3, enabled(60));
+        Score::<Test>::insert(4, ScoreState::Disabled);
+
+        assert_eq!(Ctf::leader(), Some((2, 90)));
+    });
+}
+
+#[test]
+fn leader_tie_goes_to_the_lowest_account() {
+    new_test_ext().execute_with(|| {
+        Score::<Test>::insert(7, enabled(50));
+        Score::<Test>::insert(3, enabled(50));
+        Score::<Test>::insert(5, enabled(50));
+
+        assert_eq!(Ctf::leader(), Some((3, 50)));
+    });
+}
+
+#[test]
+fn no_leader_without_enabled_accounts() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(Ctf::leader(), None);
+
+        Score::<Test>::insert(1, ScoreState::Disabled);
+        Score::<Test>::insert(2, ScoreState::Disabled);
+        assert_eq!(Ctf::leader(), None);
+    });
+}
+
+#[test]
+fn old_pow_version_is_accepted_during_the_grace_period() {
+    new_test_ext().execute_with(|| {
+        let old_work = mine(1, 8);
+        assert_ok!(Ctf::bump_pow_version(RuntimeOrigin::root(), 2));
+        assert_eq!(PowVersionGrace::<Test>::get(), Some((1, 3)));
+
+        // Both versions are accepted until the grace period ends
+        next_block();
+        next_block();
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 8, old_work));
+        System::inc_account_nonce(1);
+        let new_work = mine(1, 8);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 8, new_work));
+
+        next_block();
+        assert_eq!(PowVersionGrace::<Test>::get(), None);
+        System::inc_account_nonce(1);
+        PowVersion::<Test>::put(1);
+        let old_work = mine(1, 8);
+        PowVersion::<Test>::put(2);
+        let points_before = points(1);
+        assert_ok!(Ctf::submit_solution(RuntimeOrigin::signed(1), 8, old_work));
+        assert_eq!(points(1), points_before);
+    });
+}
+
+#[test]
+fn explicit_nonces_must_be_near_the_live_nonce() {
+    new_test_ext().execute_with(|| {
+        System::inc_account_nonce(1);
+        System::inc_account_nonce(1);
+
+        let work = Ctf::mine(&1, 4, 3);
+        assert_noop!(
+            Ctf::submit_solution_with_nonce(RuntimeOrigin::signed(1), 3, 4, work),
+            Error::<Test>::NonceOutOfWindow
+        );
+        let work = Ctf::mine(&1, 0, 3);
+        assert_noop!(
+            Ctf::submit_solution_with_nonce(RuntimeOrigin::signed(1), 3, 0, work),
+            Error::<Test>::NonceOutOfWindow
+        );
+
+        let work = Ctf::mine(&1, 1, 3);
+        assert_ok!(Ctf::submit_solution_with_nonce(
+            RuntimeOrigin::signed(1),
+            3,
+            1,
+            work
+        ));
+        assert_eq!(points(1), 4);
+    });
+}
+
+#[test]
+fn airdrop_credits_enabled_accounts_only() {
+    new_test_ext().execute_with(|| {
+        Score::<Test>::insert(1, enabled(50));
+        Score::<Test>::insert(2, ScoreState::Disabled);
+        let recipients = BoundedVec::truncate_from(vec![(1, 100), (2, 100), (3, 25)]);
+
+        assert_noop!(
+            Ctf::airdrop_points(RuntimeOrigin::signed(1), recipients.clone()),
+            BadOrigin
+        );
+        assert_ok!(Ctf::airdrop_points(RuntimeOrigin::root(), recipients));
+
+        assert_eq!(points(1), 150);
+        assert_eq!(Score::<Test>::get(2), ScoreState::Disabled);
+        assert_eq!(points(3), 25);
+        assert_eq!(Ctf::leaderboard(), vec![(1, 150), (3, 25)]);
+        System::assert_last_event(
+            Event::AirdropCompleted {
+                recipient_count: 2,
+                total_points: 125,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn draining_the_lottery_pays_the_pool_and_resets_entries() {
+    new_test_ext().execute_with(|| {
+        fill_lottery(1, 5);
+        LotteryBonusPool::<Test>::put(300);
+        Score::<Test>::insert(9, ScoreState::Disabled);
+
+        assert_noop!(
+            Ctf::drain_lottery_to(RuntimeOrigin::signed(1), 7),
+            BadOrigin
+        );
+        assert_noop!(
+            Ctf::drain_lottery_to(RuntimeOrigin::root(), 9),
+            Error::<Test>::ScoreDisabled
+        );
+
+        assert_ok!(Ctf::drain_lottery_to(RuntimeOrigin::root(), 7));
+        assert_eq!(points(7), 300);
+        assert_eq!(LotteryBonusPool::<Test>::get(), 0);
+        assert_eq!(LotteryEntryCount::<Test>::get(), 0);
+        assert_eq!(LotteryEntries::<Test>::iter_keys().count(), 0);
+        System::assert_last_event(
+            Event::LotteryDrained {
+                target: 7,
+                amount: 300,
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn recurring_lottery_draws_every_interval() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Ctf::start_recurring_lottery(RuntimeOrigin::signed(1), 3),
+            BadOrigin
+        );
+        assert_ok!(Ctf::start_recurring_lottery(RuntimeOrigin::root(), 3));
+        assert_eq!(NextLotteryBlock::<Test>::get(), Some(4));
+
+        // Far below the entry threshold, the draw still happens on schedule
+        fill_lottery(1, 2);
+        next_block();
+        next_block();
+        assert_eq!(LotteryEntryCount::<Test>::get(), 2);
+        next_block();
+        assert_eq!(LotteryEntryCount::<Test>::get(), 0);
+        assert!(System::events().iter().any(|record| matches!(
+            record.event,
+            RuntimeEvent::Ctf(Event::LotteryWinnerSelected { .. })
+        )));
+        assert_eq!(NextLotteryBlock::<Test>::get(), Some(7));
+
+        assert_ok!(Ctf::start_recurring_lottery(RuntimeOrigin::root(), 0));
+        assert_eq!(NextLotteryBlock::<Test>::get(), None);
+    });
+}
+
+#[test]
+fn score_attestation_reports_points_at_the_current_block() {
+    new_test_ext().execute_with(|| {
+        Score::<Test>::insert(1, enabled(250));
+        Score::<Test>::insert(2, ScoreState::Disabled);
+        next_block();
+
+        assert_eq!(Ctf::score_attestation(&1), Some((250, 2)));
+        assert_eq!(Ctf::score_attestation(&2), None);
+        assert_eq!(Ctf::score_attestation(&3), None);
+    });
+}
+
+#[test]
+fn practice_submissions_change_no_state() {
+    new_test_ext().execute_with(|| {
+        SubmissionQuota::set(1);
+
+        for (work, valid) in [(mine(1, 6), true), (H256::zero(), false)] {
+            assert_storage_noop!({
+                assert_ok!(Ctf::submit_practice(RuntimeOrigin::signed(1), 6, work));
+                System::assert_last_event(
+                    Event::PracticeResult {
+                        who: 1,
+                        difficulty: 6,
+                        valid,
+                    }
+                    .into(),
+                );
+                System::reset_events();
+            });
+        }
+        assert!(!Score::<Test>::contains_key(1));
+        assert_eq!(Ctf::player_count(), 0);
+
+        Score::<Test>::insert(2, ScoreState::Disabled);
+        let work = mine(2, 6);
+        assert_noop!(
+            Ctf::submit_practice(RuntimeOrigin::signed(2), 6, work),
+            Error::<Test>::ScoreDisabled
+        );
+    });
+}
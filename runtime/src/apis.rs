@@ -27,6 +27,7 @@
 use alloc::vec::Vec;
 
 use polkadot_sdk::*;
+#[cfg(feature = "try-runtime")]
 use crate::Weight;
 
 use frame_support::genesis_builder_helper::{build_state, get_preset};
@@ -37,14 +38,14 @@ use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
     traits::Block as BlockT,
     transaction_validity::{TransactionSource, TransactionValidity},
-    ApplyExtrinsicResult,
+    ApplyExtrinsicResult, DispatchError,
 };
 use sp_version::RuntimeVersion;
 
 // Local module imports
 use super::{
-    AccountId, Block, Executive, InherentDataExt, Nonce, Runtime, RuntimeGenesisConfig,
-    SessionKeys, System, SLOT_DURATION, VERSION,
+    AccountId, Block, BlockNumber, Executive, Hash, InherentDataExt, Nonce, Runtime,
+    RuntimeGenesisConfig, SessionKeys, System, SLOT_DURATION, VERSION,
 };
 
 // we move some impls outside so we can easily use them with `docify`.
@@ -158,6 +159,82 @@ impl_runtime_apis! {
         }
     }
 
+    impl pallet_ctf::runtime_api::ChallengeApi<Block, AccountId, BlockNumber, Hash> for Runtime {
+        fn recent_winners() -> Vec<(AccountId, u64, BlockNumber)> {
+            pallet_ctf::Pallet::<Runtime>::recent_winners()
+        }
+
+        fn get_histogram() -> Vec<(u32, u32)> {
+            pallet_ctf::Pallet::<Runtime>::histogram()
+        }
+
+        fn score_attestation(account: AccountId) -> Option<(u64, BlockNumber)> {
+            pallet_ctf::Pallet::<Runtime>::score_attestation(&account)
+        }
+
+        fn get_tier(account: AccountId) -> Option<pallet_ctf::BadgeTier> {
+            pallet_ctf::Pallet::<Runtime>::get_tier(&account)
+        }
+
+        fn leader() -> Option<(AccountId, u64)> {
+            pallet_ctf::Pallet::<Runtime>::leader()
+        }
+
+        fn leaderboard_raw(n: u32) -> Vec<([u8; 32], u64)> {
+            pallet_ctf::Pallet::<Runtime>::leaderboard_raw(n)
+        }
+
+        fn export_scores(start: Option<AccountId>, limit: u32) -> pallet_ctf::ScorePage<AccountId> {
+            pallet_ctf::Pallet::<Runtime>::export_scores(start, limit)
+        }
+
+        fn verification_log() -> Vec<(AccountId, u32, bool, BlockNumber)> {
+            pallet_ctf::Pallet::<Runtime>::verification_log()
+        }
+
+        fn total_withdrawn() -> u128 {
+            pallet_ctf::Pallet::<Runtime>::total_withdrawn()
+        }
+
+        fn total_burned() -> u128 {
+            pallet_ctf::Pallet::<Runtime>::total_burned()
+        }
+
+        fn debug_print_score(account: AccountId) {
+            pallet_ctf::Pallet::<Runtime>::debug_print_score(&account)
+        }
+
+        fn player_count() -> u32 {
+            pallet_ctf::Pallet::<Runtime>::player_count()
+        }
+
+        fn lottery_draw_countdown() -> u32 {
+            pallet_ctf::Pallet::<Runtime>::lottery_draw_countdown()
+        }
+
+        fn verify_batch_pow(
+            account: AccountId,
+            nonce: u32,
+            difficulty: u32,
+            candidates: Vec<Hash>,
+        ) -> Result<Vec<bool>, DispatchError> {
+            pallet_ctf::Pallet::<Runtime>::verify_batch_pow(&account, nonce, difficulty, candidates)
+                .map_err(Into::into)
+        }
+
+        fn pow_preimage(account: AccountId, nonce: u32, difficulty: u32, work: Hash) -> Vec<u8> {
+            pallet_ctf::Pallet::<Runtime>::pow_preimage(&account, nonce, difficulty, &work)
+        }
+
+        fn pow_rounds() -> u32 {
+            pallet_ctf::Pallet::<Runtime>::pow_rounds()
+        }
+
+        fn pallet_version() -> (u8, u8, u8) {
+            pallet_ctf::Pallet::<Runtime>::version()
+        }
+    }
+
     #[cfg(feature = "try-runtime")]
     impl frame_try_runtime::TryRuntime<Block> for Runtime {
         fn on_runtime_upgrade(checks: frame_try_runtime::UpgradeCheckSelect) -> (Weight, Weight) {
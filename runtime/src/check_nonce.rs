@@ -18,7 +18,9 @@
 use alloc::vec;
 use codec::{Decode, DecodeWithMemTracking, Encode};
 use frame_support::{
-    dispatch::DispatchInfo, pallet_prelude::TransactionSource, RuntimeDebugNoBound,
+    dispatch::DispatchInfo,
+    pallet_prelude::{Get, TransactionSource, ValueQuery},
+    storage_alias, Blake2_128Concat, RuntimeDebugNoBound,
 };
 use frame_system::Config;
 use polkadot_sdk::*;
@@ -26,7 +28,7 @@ use scale_info::TypeInfo;
 use sp_runtime::{
     traits::{
         AsSystemOriginSigner, DispatchInfoOf, Dispatchable, One, PostDispatchInfoOf,
-        TransactionExtension, ValidateResult, Zero,
+        TransactionExtension, UniqueSaturatedInto, ValidateResult,
     },
     transaction_validity::{
         InvalidTransaction, TransactionLongevity, TransactionValidityError, ValidTransaction,
@@ -35,6 +37,32 @@ use sp_runtime::{
 };
 use sp_weights::Weight;
 
+/// Nonces accepted in the current 256 wide window of each account, one bit per nonce.
+///
+/// Kept as a second line of defence against replays should the account nonce ever be reset, e.g.
+/// by the account being reaped and recreated. The window slides forward, clearing every bit, when
+/// the account nonce reaches the next multiple of 256.
+///
+/// `CheckNonce` is a transaction extension rather than a pallet, so the map is declared as a
+/// storage alias under the `CheckNonce` prefix. That keeps it out of the metadata; it holds 32
+/// bytes per account that has signed a transaction, and a runtime upgrade retiring it can drop
+/// the whole prefix with `clear_storage_prefix(b"CheckNonce", b"AcceptedNonceBitset", ..)`.
+#[storage_alias]
+pub type AcceptedNonceBitset<T: Config> =
+    StorageMap<CheckNonce, Blake2_128Concat, <T as Config>::AccountId, [u64; 4], ValueQuery>;
+
+/// Word and bit of `nonce` within its [`AcceptedNonceBitset`] window
+fn bitset_position<T: Config>(nonce: T::Nonce) -> (usize, u64) {
+    let index = UniqueSaturatedInto::<u64>::unique_saturated_into(nonce) % 256;
+    ((index / 64) as usize, 1 << (index % 64))
+}
+
+/// Whether `nonce` falls in the same [`AcceptedNonceBitset`] window as `current`
+fn same_window<T: Config>(nonce: T::Nonce, current: T::Nonce) -> bool {
+    let window = |n: T::Nonce| UniqueSaturatedInto::<u64>::unique_saturated_into(n) / 256;
+    window(nonce) == window(current)
+}
+
 /// Nonce check and increment to give replay protection for transactions.
 ///
 /// # Transaction Validity
@@ -52,6 +80,10 @@ use sp_weights::Weight;
 pub struct CheckNonce<T: Config>(#[codec(compact)] pub T::Nonce);
 
 impl<T: Config> CheckNonce<T> {
+    /// Number of blocks a validated transaction stays in the pool. Proofs of work are mined for
+    /// a nonce, so a solution that is not included in time should drop out rather than linger.
+    pub const LONGEVITY: TransactionLongevity = 64;
+
     /// utility constructor. Used only in client/factory code.
     pub fn from(nonce: T::Nonce) -> Self {
         Self(nonce)
@@ -100,7 +132,8 @@ where
     type Pre = Pre;
 
     fn weight(&self, _: &T::RuntimeCall) -> sp_weights::Weight {
-        Zero::zero()
+        // Reading and writing `AcceptedNonceBitset`, the account itself is already accounted for
+        T::DbWeight::get().reads_writes(1, 1)
     }
 
     fn validate(
@@ -120,6 +153,13 @@ where
         if self.0 < account.nonce {
             return Err(InvalidTransaction::Stale.into());
         }
+        // Future nonces beyond the window cannot be told apart from the current bits yet
+        if same_window::<T>(self.0, account.nonce) {
+            let (word, bit) = bitset_position::<T>(self.0);
+            if AcceptedNonceBitset::<T>::get(who)[word] & bit != 0 {
+                return Err(InvalidTransaction::Stale.into());
+            }
+        }
 
         let provides = vec![Encode::encode(&(&who, self.0))];
         let requires = if account.nonce < self.0 {
@@ -132,7 +172,7 @@ where
             priority: 0,
             requires,
             provides,
-            longevity: TransactionLongevity::MAX,
+            longevity: Self::LONGEVITY,
             propagate: true,
         };
 
@@ -161,6 +201,15 @@ where
             return Err(InvalidTransaction::Future.into());
         }
         nonce += T::Nonce::one();
+        AcceptedNonceBitset::<T>::mutate(&who, |bits| {
+            if same_window::<T>(self.0, nonce) {
+                let (word, bit) = bitset_position::<T>(self.0);
+                bits[word] |= bit;
+            } else {
+                // The account nonce reached the next window
+                *bits = [0; 4];
+            }
+        });
         frame_system::Account::<T>::mutate(who, |account| account.nonce = nonce);
         Ok(Pre::NonceChecked)
     }
@@ -178,3 +227,81 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AccountId, Runtime, RuntimeCall, RuntimeOrigin};
+    use sp_runtime::traits::TxBaseImplication;
+
+    #[test]
+    fn validated_transactions_expire_after_the_configured_longevity() {
+        sp_io::TestExternalities::default().execute_with(|| {
+            let call = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+            let (validity, _, _) = CheckNonce::<Runtime>(0)
+                .validate(
+                    RuntimeOrigin::signed(AccountId::from([1; 32])),
+                    &call,
+                    &DispatchInfo::default(),
+                    0,
+                    (),
+                    &TxBaseImplication(()),
+                    TransactionSource::External,
+                )
+                .unwrap();
+            assert_eq!(validity.longevity, CheckNonce::<Runtime>::LONGEVITY);
+        });
+    }
+
+    #[test]
+    fn weight_covers_the_bitset_access() {
+        let call = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+        assert_eq!(
+            CheckNonce::<Runtime>(0).weight(&call),
+            <Runtime as Config>::DbWeight::get().reads_writes(1, 1)
+        );
+    }
+
+    #[test]
+    fn accepted_nonces_are_rejected_until_the_window_slides() {
+        sp_io::TestExternalities::default().execute_with(|| {
+            let who = AccountId::from([1; 32]);
+            let call = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+            let validate = |nonce: u32| {
+                CheckNonce::<Runtime>(nonce).validate(
+                    RuntimeOrigin::signed(who.clone()),
+                    &call,
+                    &DispatchInfo::default(),
+                    0,
+                    (),
+                    &TxBaseImplication(()),
+                    TransactionSource::External,
+                )
+            };
+            let accept = |nonce: u32| {
+                let (_, val, origin) = validate(nonce).unwrap();
+                CheckNonce::<Runtime>(nonce)
+                    .prepare(val, &origin, &call, &DispatchInfo::default(), 0)
+                    .unwrap();
+            };
+
+            accept(0);
+            assert_eq!(AcceptedNonceBitset::<Runtime>::get(&who), [1, 0, 0, 0]);
+
+            // The account nonce is reset, the bitset still remembers nonce 0
+            frame_system::Account::<Runtime>::mutate(&who, |account| account.nonce = 0);
+            assert!(matches!(
+                validate(0),
+                Err(TransactionValidityError::Invalid(InvalidTransaction::Stale))
+            ));
+            assert!(validate(1).is_ok());
+
+            // Reaching the next window clears the bits of the previous one
+            frame_system::Account::<Runtime>::mutate(&who, |account| account.nonce = 255);
+            accept(255);
+            assert_eq!(AcceptedNonceBitset::<Runtime>::get(&who), [0; 4]);
+            accept(256);
+            assert_eq!(AcceptedNonceBitset::<Runtime>::get(&who), [1, 0, 0, 0]);
+        });
+    }
+}
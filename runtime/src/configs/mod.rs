@@ -30,7 +30,7 @@ use frame_support::{
     derive_impl,
     dispatch::DispatchClass,
     parameter_types,
-    traits::{ConstBool, ConstU32, ConstU64, VariantCountOf},
+    traits::{ConstBool, ConstU128, ConstU32, ConstU64, VariantCountOf},
     weights::{
         constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight},
         Weight,
@@ -43,16 +43,16 @@ use frame_system::{
 };
 use polkadot_runtime_common::BlockHashCount;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_runtime::Perbill;
+use sp_runtime::{BoundedVec, Perbill, Percent};
 use sp_version::RuntimeVersion;
 
 // Local module imports
 use super::{
-    AccountId, Aura, Balance, Balances, Block, BlockNumber, CollatorSelection, Hash, Nonce,
-    PalletInfo, Runtime, RuntimeCall, RuntimeEvent, RuntimeFreezeReason, RuntimeHoldReason,
-    RuntimeOrigin, RuntimeTask, Session, SessionKeys, System, AVERAGE_ON_INITIALIZE_RATIO,
-    EXISTENTIAL_DEPOSIT, HOURS, MAXIMUM_BLOCK_WEIGHT, NORMAL_DISPATCH_RATIO, SLOT_DURATION,
-    VERSION,
+    AccountId, Aura, Balance, Balances, Block, BlockNumber, CTFPallet, CollatorSelection, Hash,
+    MultiBlockMigrations, Nonce, PalletInfo, Runtime, RuntimeCall, RuntimeEvent,
+    RuntimeFreezeReason, RuntimeHoldReason, RuntimeOrigin, RuntimeTask, Session, SessionKeys,
+    System, AVERAGE_ON_INITIALIZE_RATIO, DAYS, EXISTENTIAL_DEPOSIT, HOURS, MAXIMUM_BLOCK_WEIGHT,
+    NORMAL_DISPATCH_RATIO, SLOT_DURATION, VERSION,
 };
 
 parameter_types! {
@@ -115,6 +115,8 @@ impl frame_system::Config for Runtime {
     /// The action to take on a Runtime Upgrade
     type OnSetCode = ();
     type MaxConsumers = frame_support::traits::ConstU32<16>;
+    /// Migrations that span several blocks are driven by the migrations pallet.
+    type MultiBlockMigrator = MultiBlockMigrations;
 }
 
 impl pallet_timestamp::Config for Runtime {
@@ -127,7 +129,7 @@ impl pallet_timestamp::Config for Runtime {
 
 impl pallet_authorship::Config for Runtime {
     type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Aura>;
-    type EventHandler = (CollatorSelection,);
+    type EventHandler = (CollatorSelection, CTFPallet);
 }
 
 parameter_types! {
@@ -171,6 +173,29 @@ impl pallet_utility::Config for Runtime {
     type WeightInfo = ();
 }
 
+parameter_types! {
+    pub MbmServiceWeight: Weight = Perbill::from_percent(80) * RuntimeBlockWeights::get().max_block;
+}
+
+impl pallet_migrations::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    #[cfg(not(feature = "runtime-benchmarks"))]
+    type Migrations = (
+        pallet_ctf::migrations::v2::MigrateV1ToV2<Runtime>,
+        pallet_ctf::migrations::v3::MigrateV2ToV3<Runtime>,
+        pallet_ctf::migrations::v4::MigrateV3ToV4<Runtime>,
+    );
+    // Benchmarks need mocked migrations to be able to run.
+    #[cfg(feature = "runtime-benchmarks")]
+    type Migrations = pallet_migrations::mock_helpers::MockedMigrations;
+    type CursorMaxLen = ConstU32<65_536>;
+    type IdentifierMaxLen = ConstU32<256>;
+    type MigrationStatusHandler = ();
+    type FailedMigrationHandler = frame_support::migrations::FreezeChainOnFailedMigration;
+    type MaxServiceWeight = MbmServiceWeight;
+    type WeightInfo = pallet_migrations::weights::SubstrateWeight<Runtime>;
+}
+
 impl parachain_info::Config for Runtime {}
 
 parameter_types! {
@@ -235,6 +260,60 @@ impl pallet_parachain_template::Config for Runtime {
     type WeightInfo = pallet_parachain_template::weights::SubstrateWeight<Runtime>;
 }
 
+parameter_types! {
+    pub CtfMilestones: BoundedVec<u64, ConstU32<16>> =
+        BoundedVec::truncate_from(alloc::vec![1_000, 10_000, 100_000]);
+    pub CtfTiers: BoundedVec<(u32, BoundedVec<u8, ConstU32<16>>), ConstU32<8>> =
+        BoundedVec::truncate_from(alloc::vec![
+            (20, BoundedVec::truncate_from(b"Bronze".to_vec())),
+            (24, BoundedVec::truncate_from(b"Silver".to_vec())),
+            (28, BoundedVec::truncate_from(b"Gold".to_vec())),
+        ]);
+    pub const CtfTierThresholds: [u64; 4] = [100, 1_000, 10_000, 100_000];
+    pub const CtfBurstDecay: Percent = Percent::from_percent(50);
+    pub const CtfTransferToDisabled: pallet_ctf::DisabledRecipientPolicy =
+        pallet_ctf::DisabledRecipientPolicy::Reject;
+}
+
 impl pallet_ctf::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
+    type MinDifficulty = ConstU32<20>;
+    type MaxDifficulty = ConstU32<256>;
+    type PowRounds = ConstU32<1>;
+    type PointsGranularity = ConstU64<1>;
+    type BurstDecay = CtfBurstDecay;
+    type Tiers = CtfTiers;
+    type MaxTotalScore = ConstU64<{ u64::MAX / 2 }>;
+    type LifetimeScoreCap = ConstU64<0>;
+    type Milestones = CtfMilestones;
+    type TierThresholds = CtfTierThresholds;
+    type LeaderboardSize = ConstU32<100>;
+    type MinScoreForLottery = ConstU64<0>;
+    type LotteryThreshold = ConstU32<20>;
+    type MinLotteryEntrants = ConstU32<3>;
+    type MaxRandomnessAge = ConstU32<HOURS>;
+    type LotteryDifficulty = ConstU32<25>;
+    type AllowUnsignedLottery = ConstBool<true>;
+    type LotteryEntryCost = ConstU64<0>;
+    type LotteryStake = ConstU128<0>;
+    type RolloverPrize = ConstBool<true>;
+    // The nonce check runs before dispatch, so the live nonce is one past the signed one.
+    type NonceTolerance = ConstU32<1>;
+    type SubmissionCooldown = ConstU32<0>;
+    type WithdrawCooldown = ConstU32<HOURS>;
+    type MaxTotalSubmissions = ConstU64<0>;
+    type SubmissionQuota = ConstU32<0>;
+    type QuotaPeriod = ConstU32<DAYS>;
+    // A reorg reverts the credited points together with the solution, no escrow needed.
+    type EscrowPeriod = ConstU32<0>;
+    type Randomness = pallet_ctf::SelfChainedRandomness<Runtime>;
+    type AuthorBonus = ConstU64<10>;
+    type Currency = Balances;
+    type RewardPerPoint = ConstU128<0>;
+    type TransferToDisabled = CtfTransferToDisabled;
+    type SubmissionFilter = ();
+    type VerboseVerification = ConstBool<false>;
+    type AllowImport = ConstBool<false>;
+    type AdminOrigin = EnsureRoot<AccountId>;
+    type WeightInfo = pallet_ctf::weights::SubstrateWeight<Runtime>;
 }
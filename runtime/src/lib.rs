@@ -86,9 +86,10 @@ pub type UncheckedExtrinsic =
 
 /// All migrations of the runtime, aside from the ones declared in the pallets.
 ///
-/// This can be a tuple of types, each implementing `OnRuntimeUpgrade`.
+/// This can be a tuple of types, each implementing `OnRuntimeUpgrade`. Migrations spanning
+/// several blocks are registered with `pallet_migrations` instead.
 #[allow(unused_parens)]
-type Migrations = ();
+type Migrations = (pallet_ctf::migrations::v1::MigrateV0ToV1<Runtime>,);
 
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
@@ -237,6 +238,8 @@ mod runtime {
     pub type ParachainInfo = parachain_info;
     #[runtime::pallet_index(3)]
     pub type Utility = pallet_utility;
+    #[runtime::pallet_index(4)]
+    pub type MultiBlockMigrations = pallet_migrations;
 
     // Monetary stuff.
     #[runtime::pallet_index(10)]
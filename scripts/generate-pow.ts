@@ -11,6 +11,17 @@ const DEFAULT_ACCOUNT = '5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY'; // A
 const DEFAULT_DIFFICULTY = 20;
 const DEFAULT_NONCE = 0;
 const DEFAULT_MAX_ATTEMPTS = 1000000;
+const DEFAULT_POW_VERSION = 1; // Must match the chain's PowVersion storage
+const DEFAULT_POW_ROUNDS = 1; // Must match the chain's PowRounds constant
+
+// Hash the input, rehashing the digest for every further round
+function powHash(input: Uint8Array, rounds: number): Uint8Array {
+  let hash = blake2AsU8a(input, 256);
+  for (let round = 1; round < rounds; round++) {
+    hash = blake2AsU8a(hash, 256);
+  }
+  return hash;
+}
 
 // Convert a hash byte array to U256 for comparison
 function hashToU256(hash: Uint8Array): BN {
@@ -24,9 +35,12 @@ async function findValidProofOfWork(
   nonce: number, 
   difficulty: number,
   maxAttempts = DEFAULT_MAX_ATTEMPTS,
-  silent = false
+  silent = false,
+  powVersion = DEFAULT_POW_VERSION,
+  powRounds = DEFAULT_POW_ROUNDS
 ): Promise<string | null> {
   // Convert account ID to bytes
+  const versionBytes = new Uint8Array([powVersion]);
   const accountBytes = decodeAddress(accountId);
   const nonceBytes = new Uint8Array(new BN(nonce).toArray('le', 4));
   const difficultyBytes = new Uint8Array(new BN(difficulty).toArray('le', 4));
@@ -49,10 +63,10 @@ async function findValidProofOfWork(
     const workBytes = crypto.getRandomValues(new Uint8Array(32));
     
     // Concatenate all inputs
-    const input = u8aConcat(accountBytes, nonceBytes, difficultyBytes, workBytes);
+    const input = u8aConcat(versionBytes, accountBytes, nonceBytes, difficultyBytes, workBytes);
     
     // Calculate the hash using blake2
-    const hash = blake2AsU8a(input, 256);
+    const hash = powHash(input, powRounds);
     
     // Convert hash to BN for comparison
     const hashValue = hashToU256(hash);
@@ -89,19 +103,22 @@ function verifyProofOfWork(
   accountId: string,
   nonce: number,
   difficulty: number,
-  work: string
+  work: string,
+  powVersion = DEFAULT_POW_VERSION,
+  powRounds = DEFAULT_POW_ROUNDS
 ): boolean {
   // Convert inputs to bytes
+  const versionBytes = new Uint8Array([powVersion]);
   const accountBytes = decodeAddress(accountId);
   const nonceBytes = new Uint8Array(new BN(nonce).toArray('le', 4));
   const difficultyBytes = new Uint8Array(new BN(difficulty).toArray('le', 4));
   const workBytes = hexToU8a(work);
   
   // Concatenate all inputs
-  const input = u8aConcat(accountBytes, nonceBytes, difficultyBytes, workBytes);
+  const input = u8aConcat(versionBytes, accountBytes, nonceBytes, difficultyBytes, workBytes);
   
   // Calculate the hash
-  const hash = blake2AsU8a(input, 256);
+  const hash = powHash(input, powRounds);
   // Convert hash to BN for comparison
   const hashValue = hashToU256(hash);
   
@@ -121,6 +138,8 @@ async function parseArgs() {
   let nonce = DEFAULT_NONCE;
   let difficulty = DEFAULT_DIFFICULTY;
   let maxAttempts = DEFAULT_MAX_ATTEMPTS;
+  let powVersion = DEFAULT_POW_VERSION;
+  let powRounds = DEFAULT_POW_ROUNDS;
   let verify = false;
   let workToVerify = '';
   
@@ -143,6 +162,14 @@ async function parseArgs() {
       case '-m':
         maxAttempts = parseInt(args[++i], 10);
         break;
+      case '--pow-version':
+      case '-p':
+        powVersion = parseInt(args[++i], 10);
+        break;
+      case '--rounds':
+      case '-r':
+        powRounds = parseInt(args[++i], 10);
+        break;
       case '--verify':
       case '-v':
         verify = true;
@@ -161,7 +188,7 @@ async function parseArgs() {
   
   if (verify) {
     // Verify an existing proof
-    const isValid = verifyProofOfWork(accountId, nonce, difficulty, workToVerify);
+    const isValid = verifyProofOfWork(accountId, nonce, difficulty, workToVerify, powVersion, powRounds);
     console.log(`\nVerification result: ${isValid ? 'VALID ✅' : 'INVALID ❌'}`);
     if (isValid) {
       console.log(`The provided proof-of-work is valid for the given parameters`);
@@ -173,7 +200,7 @@ async function parseArgs() {
     console.log(`Generating proof-of-work with difficulty ${difficulty}...`);
     const startTime = Date.now();
     
-    const proof = await findValidProofOfWork(accountId, nonce, difficulty, maxAttempts);
+    const proof = await findValidProofOfWork(accountId, nonce, difficulty, maxAttempts, false, powVersion, powRounds);
     
     const endTime = Date.now();
     const duration = (endTime - startTime) / 1000;
@@ -183,7 +210,7 @@ async function parseArgs() {
       console.log(`Proof: ${proof}`);
       
       // Double-check by verifying
-      const verified = verifyProofOfWork(accountId, nonce, difficulty, proof);
+      const verified = verifyProofOfWork(accountId, nonce, difficulty, proof, powVersion, powRounds);
       console.log(`Self-verification: ${verified ? 'VALID ✅' : 'INVALID ❌'}`);
     } else {
       console.log(`\n❌ Failed to generate a valid proof-of-work in ${duration.toFixed(2)} seconds`);
@@ -207,6 +234,8 @@ Options:
   --nonce, -n         Account nonce (default: ${DEFAULT_NONCE})
   --difficulty, -d    Difficulty level (default: ${DEFAULT_DIFFICULTY})
   --max-attempts, -m  Maximum attempts (default: ${DEFAULT_MAX_ATTEMPTS})
+  --pow-version, -p   PoW hash version, read the chain's PowVersion storage (default: ${DEFAULT_POW_VERSION})
+  --rounds, -r        Hashing rounds, read the chain's PowRounds constant (default: ${DEFAULT_POW_ROUNDS})
   --verify, -v        Verify a proof instead of generating (provide the proof)
   --help, -h          Show this help message
 